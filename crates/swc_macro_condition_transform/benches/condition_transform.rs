@@ -0,0 +1,87 @@
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use serde_json::json;
+use swc_core::{
+    common::{FileName, SourceMap, comments::SingleThreadedComments, sync::Lrc},
+    ecma::{
+        ast::Module,
+        parser::{Parser, StringInput, Syntax},
+        visit::VisitMutWith,
+    },
+};
+use swc_macro_condition_transform::{Options, condition_transform};
+use swc_macro_parser::MacroParser;
+
+/// A module of `regions` functions in `if` regions, half of them removed, each also inlining a
+/// value in the body of a kept function
+fn source(regions: usize) -> String {
+    let mut source = String::new();
+    for i in 0..regions {
+        let flag = if i % 2 == 0 { "on" } else { "off" };
+        source.push_str(&format!(
+            "/* @common:if [condition=\"features.{flag}\"] */\n\
+             export function f{i}() {{\n  \
+               return [/* @common:define-inline [value=\"build.target\"] */ \"dev\", {i}];\n\
+             }}\n\
+             /* @common:endif */\n"
+        ));
+    }
+    source
+}
+
+fn parse(cm: &SourceMap, source: &str) -> (Module, SingleThreadedComments) {
+    let fm = cm.new_source_file(FileName::Anon.into(), source.to_string());
+    let comments = SingleThreadedComments::default();
+    let module = Parser::new(
+        Syntax::Es(Default::default()),
+        StringInput::from(&*fm),
+        Some(&comments),
+    )
+    .parse_module()
+    .expect("should parse the benchmark source");
+    (module, comments)
+}
+
+fn bench_condition_transform(c: &mut Criterion) {
+    let meta_data = json!({
+        "features": { "on": true, "off": false },
+        "build": { "target": "production" },
+    });
+    let parser = MacroParser::new("common");
+
+    let mut group = c.benchmark_group("condition_transform");
+    for regions in [1_000, 4_000] {
+        let cm: Lrc<SourceMap> = Default::default();
+        let source = source(regions);
+        group.throughput(Throughput::Elements(regions as u64));
+        // Only the transform is measured, the parsing is redone for each run as it takes the macro
+        // comments
+        group.bench_with_input(
+            BenchmarkId::from_parameter(regions),
+            &source,
+            |b, source| {
+                b.iter_batched(
+                    || {
+                        let (module, comments) = parse(&cm, source);
+                        (module, parser.parse(&comments))
+                    },
+                    |(mut module, macros)| {
+                        let (mut transformer, _) = condition_transform(
+                            meta_data.clone(),
+                            macros,
+                            None,
+                            Options::default(),
+                        )
+                        .expect("should transform the benchmark source");
+                        module.visit_mut_with(&mut transformer);
+                        module
+                    },
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_condition_transform);
+criterion_main!(benches);
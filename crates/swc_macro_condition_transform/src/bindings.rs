@@ -0,0 +1,69 @@
+use rustc_hash::FxHashSet;
+use swc_core::{
+    atoms::Atom,
+    ecma::{
+        ast::{
+            ArrowExpr, BindingIdent, Class, ClassDecl, DefaultDecl, FnDecl, Function,
+            ImportSpecifier, ModuleDecl, Stmt, VarDeclarator,
+        },
+        visit::{Visit, VisitWith},
+    },
+};
+
+/// Collect the names declared by the statements of a list, without looking into nested scopes
+#[derive(Default)]
+pub struct DeclaredNames(pub FxHashSet<Atom>);
+
+impl Visit for DeclaredNames {
+    fn visit_stmt(&mut self, node: &Stmt) {
+        if let Stmt::Decl(decl) = node {
+            decl.visit_with(self);
+        }
+    }
+
+    fn visit_module_decl(&mut self, node: &ModuleDecl) {
+        match node {
+            ModuleDecl::Import(import) => {
+                self.0
+                    .extend(import.specifiers.iter().map(|specifier| match specifier {
+                        ImportSpecifier::Named(named) => named.local.sym.clone(),
+                        ImportSpecifier::Default(default) => default.local.sym.clone(),
+                        ImportSpecifier::Namespace(namespace) => namespace.local.sym.clone(),
+                    }))
+            }
+            ModuleDecl::ExportDecl(export) => export.decl.visit_with(self),
+            ModuleDecl::ExportDefaultDecl(export) => {
+                let ident = match &export.decl {
+                    DefaultDecl::Fn(fn_expr) => fn_expr.ident.as_ref(),
+                    DefaultDecl::Class(class_expr) => class_expr.ident.as_ref(),
+                    DefaultDecl::TsInterfaceDecl(_) => None,
+                };
+                self.0.extend(ident.map(|ident| ident.sym.clone()));
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_var_declarator(&mut self, node: &VarDeclarator) {
+        node.name.visit_with(self);
+    }
+
+    fn visit_binding_ident(&mut self, node: &BindingIdent) {
+        self.0.insert(node.sym.clone());
+    }
+
+    fn visit_fn_decl(&mut self, node: &FnDecl) {
+        self.0.insert(node.ident.sym.clone());
+    }
+
+    fn visit_class_decl(&mut self, node: &ClassDecl) {
+        self.0.insert(node.ident.sym.clone());
+    }
+
+    // The default values of patterns may contain nested scopes
+    fn visit_function(&mut self, _: &Function) {}
+
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+    fn visit_class(&mut self, _: &Class) {}
+}
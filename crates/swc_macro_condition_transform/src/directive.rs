@@ -4,17 +4,117 @@ use swc_core::common::{BytePos, Span};
 pub enum Directive {
     If(IfDirective),
     DefineInline(DefineInlineDirective),
+    Define(DefineDirective),
+    Replace(ReplaceDirective),
+    IfNext(IfNextDirective),
+    Report(ReportDirective),
+    Inject(InjectDirective),
+    DefineInlineText(DefineInlineTextDirective),
+    Rename(RenameDirective),
+    ImportSwap(ImportSwapDirective),
 }
 
 #[derive(Debug)]
 pub struct IfDirective {
+    pub span: Span,
     pub range: Span,
     pub condition: String,
+    /// `if` or `unless`
+    pub name: &'static str,
+    /// Keep the range when the condition is falsy instead, as `unless` or `negate="true"` does
+    pub negate: bool,
+    /// Keep the declarations of the exports in the range when it is removed
+    pub keep_decl: bool,
+    /// Replace the call arguments in the range rather than dropping them when it is removed,
+    /// for calls where the position of an argument matters
+    pub null_args: bool,
+}
+
+/// Like `IfDirective`, but only covers the statement right after `pos`
+#[derive(Debug)]
+pub struct IfNextDirective {
+    pub span: Span,
+    pub pos: BytePos,
+    pub condition: String,
 }
 
 #[derive(Debug)]
 pub struct DefineInlineDirective {
+    pub span: Span,
+    pub pos: BytePos,
+    pub value: String,
+    pub default: Option<String>,
+    /// Exposes the resolved value to later directives as `inline.<name>`
+    pub name: Option<String>,
+    /// Declare `const <name> = <value>;` before the statement right after `pos` instead of
+    /// replacing the expression there
+    pub as_const: bool,
+    /// Inline JSON `null` as `undefined` rather than `null`
+    pub undefined_for_null: bool,
+}
+
+/// Substitutes `placeholder` in the string and template literals of the statement right after `pos`
+#[derive(Debug)]
+pub struct DefineInlineTextDirective {
+    pub span: Span,
+    pub pos: BytePos,
+    pub value: String,
+    pub placeholder: String,
+    pub default: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct DefineDirective {
+    pub span: Span,
     pub pos: BytePos,
+    pub name: String,
     pub value: String,
     pub default: Option<String>,
 }
+
+#[derive(Debug)]
+pub struct ReplaceDirective {
+    pub span: Span,
+    pub range: Span,
+    pub with: String,
+}
+
+/// `error` or `warn` directive asserting on the metadata
+#[derive(Debug)]
+pub struct ReportDirective {
+    pub span: Span,
+    pub pos: BytePos,
+    pub is_error: bool,
+    pub condition: String,
+    pub message: String,
+}
+
+/// Inserts the snippet at `code` before the statement right after `pos` when the condition holds
+#[derive(Debug)]
+pub struct InjectDirective {
+    pub span: Span,
+    pub pos: BytePos,
+    pub condition: String,
+    pub code: String,
+}
+
+/// Renames the top-level binding `from` and its references to the identifier at `to`
+#[derive(Debug)]
+pub struct RenameDirective {
+    pub span: Span,
+    pub pos: BytePos,
+    pub from: String,
+    pub to: String,
+}
+
+/// Swaps the module specifier `from` for `to` in the statement right after `pos`, or in the whole
+/// module with `scope="module"`, when the condition holds
+#[derive(Debug)]
+pub struct ImportSwapDirective {
+    pub span: Span,
+    pub pos: BytePos,
+    pub condition: String,
+    pub from: String,
+    pub to: String,
+    pub module_scope: bool,
+}
@@ -0,0 +1,253 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use serde::Serialize;
+use swc_core::common::{SourceMap, Span};
+
+/// Errors raised while pairing and parsing the macro directives.
+/// Every variant carries the span of the offending directive comment,
+/// and its line/column when a `SourceMap` was available to resolve it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConditionTransformError {
+    /// A region directive like `if` without a matching end directive like `endif`
+    UnclosedRegion {
+        directive: String,
+        span: Span,
+        loc: Option<LineCol>,
+    },
+    /// An end directive like `endif` without a matching region directive like `if`
+    StrayRegionEnd {
+        directive: String,
+        span: Span,
+        loc: Option<LineCol>,
+    },
+    /// A directive is missing a required attribute
+    MissingAttr {
+        directive: String,
+        attr: &'static str,
+        span: Span,
+        loc: Option<LineCol>,
+    },
+    /// An `error` directive whose condition holds
+    Reported {
+        message: String,
+        span: Span,
+        loc: Option<LineCol>,
+    },
+    /// The same constant is declared by more than one define directive
+    DuplicateDefine {
+        name: String,
+        span: Span,
+        loc: Option<LineCol>,
+    },
+    /// Paths missing from the metadata in strict mode, in document order. The transform never
+    /// fails with an empty list.
+    UnknownPaths { paths: Vec<UnknownPath> },
+}
+
+/// A metadata path referenced by a directive but missing from the metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownPath {
+    pub path: String,
+    pub directive: String,
+    /// Paths with the missing key swapped for its nearest existing siblings, nearest first
+    pub suggestions: Vec<String>,
+    pub span: Span,
+    pub loc: Option<LineCol>,
+}
+
+/// 1-based line and column of a directive in the original source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl LineCol {
+    pub fn lookup(source_map: &SourceMap, span: Span) -> Self {
+        let loc = source_map.lookup_char_pos(span.lo);
+        LineCol {
+            line: loc.line,
+            col: loc.col.0 + 1,
+        }
+    }
+}
+
+impl ConditionTransformError {
+    /// The span of the offending directive, or of the first unknown path. `None` only for
+    /// `UnknownPaths` without any path.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ConditionTransformError::UnclosedRegion { span, .. }
+            | ConditionTransformError::StrayRegionEnd { span, .. }
+            | ConditionTransformError::MissingAttr { span, .. }
+            | ConditionTransformError::DuplicateDefine { span, .. }
+            | ConditionTransformError::Reported { span, .. } => Some(*span),
+            ConditionTransformError::UnknownPaths { paths } => paths.first().map(|path| path.span),
+        }
+    }
+
+    pub fn loc(&self) -> Option<LineCol> {
+        match self {
+            ConditionTransformError::UnclosedRegion { loc, .. }
+            | ConditionTransformError::StrayRegionEnd { loc, .. }
+            | ConditionTransformError::MissingAttr { loc, .. }
+            | ConditionTransformError::DuplicateDefine { loc, .. }
+            | ConditionTransformError::Reported { loc, .. } => *loc,
+            ConditionTransformError::UnknownPaths { paths } => {
+                paths.first().and_then(|path| path.loc)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConditionTransformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConditionTransformError::UnclosedRegion {
+                directive,
+                span,
+                loc,
+            } => {
+                let at = Location(*span, *loc);
+                write!(f, "{directive} at {at} has no matching end{directive}")
+            }
+            ConditionTransformError::StrayRegionEnd {
+                directive,
+                span,
+                loc,
+            } => {
+                let at = Location(*span, *loc);
+                let opening = directive.strip_prefix("end").unwrap_or(directive);
+                write!(f, "{directive} at {at} has no matching {opening}")
+            }
+            ConditionTransformError::MissingAttr {
+                directive,
+                attr,
+                span,
+                loc,
+            } => {
+                let at = Location(*span, *loc);
+                write!(f, "{directive} at {at} is missing the `{attr}` attr")
+            }
+            ConditionTransformError::Reported { message, span, loc } => {
+                let at = Location(*span, *loc);
+                write!(f, "error at {at}: {message}")
+            }
+            ConditionTransformError::DuplicateDefine { name, span, loc } => {
+                let at = Location(*span, *loc);
+                write!(f, "define at {at} redeclares `{name}`")
+            }
+            ConditionTransformError::UnknownPaths { paths } => {
+                write!(f, "unknown metadata paths:")?;
+                for (i, path) in paths.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { "," };
+                    let at = Location(path.span, path.loc);
+                    write!(f, "{sep} `{}` in {} at {at}", path.path, path.directive)?;
+                    if let Some(suggestion) = path.suggestions.first() {
+                        write!(f, " (did you mean `{suggestion}`?)")?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConditionTransformError {}
+
+/// A recoverable problem found while evaluating the directives.
+/// The transform still runs, leaving the affected code untouched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+    pub loc: Option<LineCol>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = Location(self.span, self.loc);
+        write!(f, "{} (at {at})", self.message)
+    }
+}
+
+/// The diagnostics of a transform, shared with its pass as some are only found while it runs.
+/// They are complete once the pass has been applied.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics(Rc<RefCell<Vec<Diagnostic>>>);
+
+impl Diagnostics {
+    pub(crate) fn new(diagnostics: Vec<Diagnostic>) -> Self {
+        Diagnostics(Rc::new(RefCell::new(diagnostics)))
+    }
+
+    pub(crate) fn push(&self, diagnostic: Diagnostic) {
+        self.0.borrow_mut().push(diagnostic);
+    }
+
+    /// Take the diagnostics found so far
+    pub fn take(&self) -> Vec<Diagnostic> {
+        self.0.take()
+    }
+}
+
+/// Prints `line:col` when resolved, otherwise falls back to the byte range
+struct Location(Span, Option<LineCol>);
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(LineCol { line, col }) => write!(f, "{line}:{col}"),
+            None => write!(f, "{}..{}", self.0.lo.0, self.0.hi.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::{BytePos, DUMMY_SP};
+
+    use super::*;
+
+    #[test]
+    fn unknown_paths_without_any_path() {
+        let err = ConditionTransformError::UnknownPaths { paths: Vec::new() };
+
+        assert_eq!(err.span(), None);
+        assert_eq!(err.loc(), None);
+        assert_eq!(err.to_string(), "unknown metadata paths:");
+    }
+
+    #[test]
+    fn unknown_paths_are_located_at_the_first_path() {
+        let path = |lo, line| UnknownPath {
+            path: "features.betaa".to_string(),
+            directive: "if".to_string(),
+            suggestions: vec!["features.beta".to_string()],
+            span: Span::new(BytePos(lo), BytePos(lo + 10)),
+            loc: Some(LineCol { line, col: 1 }),
+        };
+        let err = ConditionTransformError::UnknownPaths {
+            paths: vec![path(5, 1), path(30, 3)],
+        };
+
+        assert_eq!(err.span(), Some(Span::new(BytePos(5), BytePos(15))));
+        assert_eq!(err.loc(), Some(LineCol { line: 1, col: 1 }));
+        assert_eq!(
+            err.to_string(),
+            "unknown metadata paths: `features.betaa` in if at 1:1 (did you mean `features.beta`?), \
+             `features.betaa` in if at 3:1 (did you mean `features.beta`?)"
+        );
+    }
+
+    #[test]
+    fn errors_fall_back_to_the_byte_range() {
+        let err = ConditionTransformError::StrayRegionEnd {
+            directive: "endif".to_string(),
+            span: DUMMY_SP,
+            loc: None,
+        };
+
+        assert_eq!(err.to_string(), "endif at 0..0 has no matching if");
+    }
+}
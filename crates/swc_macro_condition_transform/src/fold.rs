@@ -0,0 +1,161 @@
+use swc_core::{
+    common::{DUMMY_SP, SyntaxContext, util::take::Take},
+    ecma::{
+        ast::{
+            ArrowExpr, BinaryOp, BlockStmt, Bool, Class, Decl, Expr, Function, Ident, Lit, Number,
+            Pat, Stmt, UnaryOp, VarDecl, VarDeclKind, VarDeclarator,
+        },
+        utils::find_pat_ids,
+        visit::{Visit, VisitMut, VisitMutPass, VisitMutWith, VisitWith, visit_mut_pass},
+    },
+};
+
+/// Collapse the branches whose test is a boolean or number literal introduced by the transform,
+/// e.g. `if (false) { ... }` or `0 && x` left behind by `define-inline`.
+///
+/// Literals written in the source are left to the minifier. The ones introduced by the
+/// transform are told apart by their dummy span, as metadata has no position in the source.
+pub fn constant_folding() -> VisitMutPass<ConstantFolder> {
+    visit_mut_pass(ConstantFolder)
+}
+
+pub struct ConstantFolder;
+
+impl VisitMut for ConstantFolder {
+    fn visit_mut_stmt(&mut self, node: &mut Stmt) {
+        node.visit_mut_children_with(self);
+
+        let Stmt::If(if_stmt) = node else {
+            return;
+        };
+        let Some(test) = introduced_truthiness(&if_stmt.test) else {
+            return;
+        };
+        let (cons, alt) = (*if_stmt.cons.take(), if_stmt.alt.take().map(|alt| *alt));
+        let (live, dead) = match test {
+            true => (Some(cons), alt),
+            false => (alt, Some(cons)),
+        };
+        let hoisted = dead.map(|dead| hoisted_vars(&dead)).unwrap_or_default();
+        *node = with_hoisted_vars(live, hoisted);
+    }
+
+    fn visit_mut_expr(&mut self, node: &mut Expr) {
+        node.visit_mut_children_with(self);
+
+        match node {
+            Expr::Cond(cond) => {
+                let Some(test) = introduced_truthiness(&cond.test) else {
+                    return;
+                };
+                let live = if test { &mut cond.cons } else { &mut cond.alt };
+                *node = *live.take();
+            }
+            Expr::Bin(bin) if matches!(bin.op, BinaryOp::LogicalAnd | BinaryOp::LogicalOr) => {
+                let Some(left) = introduced_truthiness(&bin.left) else {
+                    return;
+                };
+                // `false && x` and `true || x` short-circuit to the left side, as do `0 && x` and
+                // `1 || x`
+                let short_circuit = left == (bin.op == BinaryOp::LogicalOr);
+                let live = if short_circuit {
+                    &mut bin.left
+                } else {
+                    &mut bin.right
+                };
+                *node = *live.take();
+            }
+            Expr::Unary(unary) if unary.op == UnaryOp::Bang => {
+                let Some(arg) = introduced_truthiness(&unary.arg) else {
+                    return;
+                };
+                *node = Expr::Lit(Lit::Bool(Bool {
+                    span: DUMMY_SP,
+                    value: !arg,
+                }));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The truthiness of a boolean or number literal without a position in the source
+fn introduced_truthiness(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::Lit(Lit::Bool(Bool { span, value })) if span.is_dummy() => Some(*value),
+        Expr::Lit(Lit::Num(Number { span, value, .. })) if span.is_dummy() => {
+            Some(*value != 0.0 && !value.is_nan())
+        }
+        Expr::Paren(paren) => introduced_truthiness(&paren.expr),
+        _ => None,
+    }
+}
+
+/// The names declared by the `var`s of a statement, which are hoisted out of it and still bound
+/// when it is dropped
+fn hoisted_vars(stmt: &Stmt) -> Vec<Ident> {
+    let mut vars = HoistedVars::default();
+    stmt.visit_with(&mut vars);
+    vars.0
+}
+
+#[derive(Default)]
+struct HoistedVars(Vec<Ident>);
+
+impl Visit for HoistedVars {
+    fn visit_var_decl(&mut self, node: &VarDecl) {
+        if node.kind != VarDeclKind::Var {
+            return;
+        }
+        for decl in &node.decls {
+            for ident in find_pat_ids::<_, Ident>(&decl.name) {
+                if !self.0.iter().any(|var| var.sym == ident.sym) {
+                    self.0.push(ident);
+                }
+            }
+        }
+    }
+
+    fn visit_function(&mut self, _: &Function) {}
+
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+    fn visit_class(&mut self, _: &Class) {}
+}
+
+/// The live branch of a folded `if`, declaring the `vars` of the dead one without initializers,
+/// e.g. `var x;` for `if (false) { var x = 1; }`
+fn with_hoisted_vars(live: Option<Stmt>, vars: Vec<Ident>) -> Stmt {
+    if vars.is_empty() {
+        return live.unwrap_or_else(Stmt::dummy);
+    }
+
+    let decl = Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        kind: VarDeclKind::Var,
+        declare: false,
+        decls: vars
+            .into_iter()
+            .map(|ident| VarDeclarator {
+                span: DUMMY_SP,
+                name: Pat::Ident(ident.into()),
+                init: None,
+                definite: false,
+            })
+            .collect(),
+    })));
+    match live {
+        None => decl,
+        // The `var`s are hoisted out of the block anyway
+        Some(Stmt::Block(mut block)) => {
+            block.stmts.insert(0, decl);
+            Stmt::Block(block)
+        }
+        Some(live) => Stmt::Block(BlockStmt {
+            span: DUMMY_SP,
+            ctxt: SyntaxContext::empty(),
+            stmts: vec![decl, live],
+        }),
+    }
+}
@@ -0,0 +1,50 @@
+use swc_core::{
+    atoms::Atom,
+    ecma::{
+        ast::{CallExpr, Callee, Expr, ImportDecl, Lit, Str},
+        visit::{VisitMut, VisitMutWith},
+    },
+};
+
+/// Swap the module specifier `from` for `to` in imports, `import()`, `require()` and
+/// `__webpack_require__()`
+pub struct ImportSwapper {
+    pub from: String,
+    pub to: String,
+}
+
+impl ImportSwapper {
+    fn swap(&self, src: &mut Str) {
+        if &*src.value != self.from.as_str() {
+            return;
+        }
+        src.value = Atom::from(&*self.to);
+        // Let the codegen quote the new value
+        src.raw = None;
+    }
+}
+
+impl VisitMut for ImportSwapper {
+    fn visit_mut_import_decl(&mut self, node: &mut ImportDecl) {
+        self.swap(&mut node.src);
+    }
+
+    fn visit_mut_call_expr(&mut self, node: &mut CallExpr) {
+        let is_require = match &node.callee {
+            Callee::Import(_) => true,
+            Callee::Expr(callee) => matches!(
+                &**callee,
+                Expr::Ident(ident) if ident.sym == "require" || ident.sym == "__webpack_require__"
+            ),
+            Callee::Super(_) => false,
+        };
+        if is_require
+            && let Some(arg) = node.args.first_mut()
+            && arg.spread.is_none()
+            && let Expr::Lit(Lit::Str(src)) = &mut *arg.expr
+        {
+            self.swap(src);
+        }
+        node.visit_mut_children_with(self);
+    }
+}
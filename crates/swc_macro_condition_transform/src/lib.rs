@@ -1,124 +1,1407 @@
+use std::cmp::Reverse;
+
 use rustc_hash::FxHashSet;
-use swc_core::ecma::ast::{ModuleItem, Expr, Stmt};
+use swc_core::ecma::ast::{
+    ArrowExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, ClassDecl, ClassExpr, ClassMember, Decl,
+    DefaultDecl, Expr, ExprOrSpread, FnDecl, FnExpr, ForHead, Ident, Module, ModuleDecl,
+    ModuleItem, Pat, PropOrSpread, ReturnStmt, Script, Stmt, SwitchCase, Tpl, VarDecl, VarDeclKind,
+    VarDeclOrExpr, VarDeclarator,
+};
 use swc_core::{
-    common::{BytePos, Span, Spanned},
-    ecma::{
-        visit::{VisitMut, VisitMutPass, VisitMutWith, visit_mut_pass},
-    },
+    atoms::Atom,
+    common::{BytePos, DUMMY_SP, SourceMap, Span, Spanned, SyntaxContext},
+    ecma::visit::{VisitMut, VisitMutPass, VisitMutWith, VisitWith, visit_mut_pass},
 };
 use swc_macro_parser::MacroNode;
 
+pub use crate::error::{ConditionTransformError, Diagnostic, Diagnostics, LineCol, UnknownPath};
+pub use crate::fold::{ConstantFolder, constant_folding};
+pub use crate::meta_data::{FromSwcAst, Metadata, OwnedMetadata, ToSwcAst, inject_env, merge};
+pub use crate::options::{Options, RemovedExprReplacement};
+pub use crate::report::{
+    DiagnosticReport, InlineReport, RegionReport, RemovedCounts, ReportSpan, SkippedReport,
+    TransformReport, TransformReportHandle, UnknownPathReport,
+};
 use crate::{
-    directive::{DefineInlineDirective, Directive, IfDirective},
-    meta_data::{Metadata, ToSwcAst},
+    bindings::DeclaredNames,
+    directive::{
+        DefineDirective, DefineInlineDirective, DefineInlineTextDirective, Directive, IfDirective,
+        IfNextDirective, ImportSwapDirective, InjectDirective, RenameDirective, ReplaceDirective,
+        ReportDirective,
+    },
+    import_swap::ImportSwapper,
+    meta_data::{condition_paths, suggest_paths},
+    rename::{Rename, ScopedRenamer},
+    snippet::parse_snippet,
+    text::TextReplacer,
 };
 
+mod bindings;
 mod directive;
+mod error;
+mod fold;
+mod import_swap;
 mod meta_data;
+mod options;
+mod rename;
+mod report;
+mod snippet;
+mod text;
 
+/// Pair and evaluate the directives against `meta_data`, returning the pass that applies them
+/// along with the recoverable diagnostics found during evaluation and while the pass runs.
+///
+/// Removal wins over the other directives: a replacement or insertion positioned inside a
+/// removed region is skipped.
+///
+/// `source_map` is only used to resolve the line/column of directives in errors and diagnostics.
 pub fn condition_transform(
+    meta_data: serde_json::Value,
+    macros: Vec<(BytePos, MacroNode)>,
+    source_map: Option<&SourceMap>,
+    options: Options,
+) -> Result<(VisitMutPass<RemoveReplaceTransformer>, Diagnostics), ConditionTransformError> {
+    condition_transform_with_report(meta_data, macros, source_map, options)
+        .map(|(transformer, diagnostics, _)| (transformer, diagnostics))
+}
+
+/// Like `condition_transform`, additionally returning the report of what the transform does.
+/// The report is complete once the pass has been applied.
+pub fn condition_transform_with_report(
     meta_data: serde_json::Value,
     mut macros: Vec<(BytePos, MacroNode)>,
-) -> VisitMutPass<RemoveReplaceTransformer> {
-    macros.sort_by_key(|m| m.0);
+    source_map: Option<&SourceMap>,
+    options: Options,
+) -> Result<
+    (
+        VisitMutPass<RemoveReplaceTransformer>,
+        Diagnostics,
+        TransformReportHandle,
+    ),
+    ConditionTransformError,
+> {
+    // A leading comment is attached to the start of the next node, and a trailing one to the end
+    // of the previous node, so a region covers exactly the nodes between its comments either way.
+    // Ties are broken by the comment position, as leading comments come before trailing ones.
+    macros.sort_by_key(|(pos, macro_node)| (*pos, macro_node.span.lo));
+    let locate = |span: Span| source_map.map(|cm| LineCol::lookup(cm, span));
+    let evaluate = |condition: &str| {
+        if options.strict_bool {
+            meta_data.evaluate_bool(condition)
+        } else {
+            meta_data.evaluate_bool_js(condition)
+        }
+    };
 
     // Parse untyped macro nodes to directives
+    let mut diagnostics = Vec::new();
     let mut directives = Vec::new();
-    let mut if_stack = Vec::new();
+    let mut region_stack: Vec<(BytePos, MacroNode)> = Vec::new();
     for (ast_pos, macro_node) in macros {
         match macro_node.directive.as_str() {
-            "if" => if_stack.push((
-                ast_pos,
-                macro_node
-                    .attrs
-                    .get("condition")
-                    .expect("No `condition` attr in if directive")
-                    .clone(),
-            )),
-            "endif" => {
-                let (start_pos, condition) = if_stack.pop().expect("Unpaired :if directive");
-                directives.push(Directive::If(IfDirective {
-                    range: Span::new(start_pos, ast_pos),
-                    condition,
-                }));
-            }
-            "define-inline" => directives.push(Directive::DefineInline(DefineInlineDirective {
+            "if" | "unless" | "replace" => region_stack.push((ast_pos, macro_node)),
+            "endif" | "endunless" | "endreplace" => {
+                let opening = &macro_node.directive["end".len()..];
+                let Some((start_pos, open_node)) =
+                    region_stack.pop_if(|(_, open_node)| open_node.directive == opening)
+                else {
+                    return Err(ConditionTransformError::StrayRegionEnd {
+                        directive: macro_node.directive.clone(),
+                        span: macro_node.span,
+                        loc: locate(macro_node.span),
+                    });
+                };
+                let range = Span::new(start_pos, ast_pos);
+                directives.push(match opening {
+                    "if" | "unless" => Directive::If(IfDirective {
+                        span: open_node.span,
+                        range,
+                        condition: required_attr(&open_node, "condition", locate)?,
+                        name: if opening == "unless" { "unless" } else { "if" },
+                        // `negate="true"` flips the region, so a negated unless keeps it when
+                        // the condition holds
+                        negate: (opening == "unless")
+                            != open_node.attrs.get("negate").is_some_and(|v| v == "true"),
+                        keep_decl: open_node
+                            .attrs
+                            .get("keep-decl")
+                            .is_some_and(|v| v == "true"),
+                        null_args: open_node.attrs.get("mode").is_some_and(|v| v == "null"),
+                    }),
+                    _ => Directive::Replace(ReplaceDirective {
+                        span: open_node.span,
+                        range,
+                        with: required_attr(&open_node, "with", locate)?,
+                    }),
+                });
+            }
+            "if-next" => directives.push(Directive::IfNext(IfNextDirective {
+                span: macro_node.span,
+                pos: ast_pos,
+                condition: required_attr(&macro_node, "condition", locate)?,
+            })),
+            "error" | "warn" => directives.push(Directive::Report(ReportDirective {
+                span: macro_node.span,
+                pos: ast_pos,
+                is_error: macro_node.directive == "error",
+                condition: required_attr(&macro_node, "condition", locate)?,
+                message: required_attr(&macro_node, "message", locate)?,
+            })),
+            "define-inline" => {
+                let as_const = macro_node.attrs.get("as").is_some_and(|v| v == "const");
+                directives.push(Directive::DefineInline(DefineInlineDirective {
+                    span: macro_node.span,
+                    pos: ast_pos,
+                    value: required_attr(&macro_node, "value", locate)?,
+                    default: macro_node.attrs.get("default").cloned(),
+                    name: match as_const {
+                        true => Some(required_attr(&macro_node, "name", locate)?),
+                        false => macro_node.attrs.get("name").cloned(),
+                    },
+                    as_const,
+                    undefined_for_null: macro_node
+                        .attrs
+                        .get("null")
+                        .is_some_and(|v| v == "undefined"),
+                }))
+            }
+            "define-inline-text" => {
+                directives.push(Directive::DefineInlineText(DefineInlineTextDirective {
+                    span: macro_node.span,
+                    pos: ast_pos,
+                    value: required_attr(&macro_node, "value", locate)?,
+                    placeholder: required_attr(&macro_node, "placeholder", locate)?,
+                    default: macro_node.attrs.get("default").cloned(),
+                }))
+            }
+            "define" => directives.push(Directive::Define(DefineDirective {
+                span: macro_node.span,
                 pos: ast_pos,
-                value: macro_node
-                    .attrs
-                    .get("value")
-                    .expect("No `value` attr in define-inline directive")
-                    .clone(),
+                name: required_attr(&macro_node, "name", locate)?,
+                value: required_attr(&macro_node, "value", locate)?,
                 default: macro_node.attrs.get("default").cloned(),
             })),
-            _ => continue,
+            "inject" => directives.push(Directive::Inject(InjectDirective {
+                span: macro_node.span,
+                pos: ast_pos,
+                condition: required_attr(&macro_node, "condition", locate)?,
+                code: required_attr(&macro_node, "code", locate)?,
+            })),
+            "rename" => directives.push(Directive::Rename(RenameDirective {
+                span: macro_node.span,
+                pos: ast_pos,
+                from: required_attr(&macro_node, "from", locate)?,
+                to: required_attr(&macro_node, "to", locate)?,
+            })),
+            "import-swap" => directives.push(Directive::ImportSwap(ImportSwapDirective {
+                span: macro_node.span,
+                pos: ast_pos,
+                condition: required_attr(&macro_node, "condition", locate)?,
+                from: required_attr(&macro_node, "from", locate)?,
+                to: required_attr(&macro_node, "to", locate)?,
+                module_scope: macro_node.attrs.get("scope").is_some_and(|v| v == "module"),
+            })),
+            _ => diagnostics.push(Diagnostic {
+                message: format!("unknown directive `{}` is ignored", macro_node.directive),
+                span: macro_node.span,
+                loc: locate(macro_node.span),
+            }),
         }
     }
+    if let Some((_, open_node)) = region_stack.pop() {
+        return Err(ConditionTransformError::UnclosedRegion {
+            directive: open_node.directive,
+            span: open_node.span,
+            loc: locate(open_node.span),
+        });
+    }
+
+    // Collect the missing paths for strict mode, conditions are only evaluated as bool so a
+    // missing path would silently be falsy
+    let mut unknown_paths = Vec::new();
+    for directive in &directives {
+        let (condition, name, span) = match directive {
+            Directive::If(d) => (&d.condition, d.name, d.span),
+            Directive::IfNext(d) => (&d.condition, "if-next", d.span),
+            Directive::Report(d) => (
+                &d.condition,
+                if d.is_error { "error" } else { "warn" },
+                d.span,
+            ),
+            Directive::Inject(d) => (&d.condition, "inject", d.span),
+            Directive::ImportSwap(d) => (&d.condition, "import-swap", d.span),
+            _ => continue,
+        };
+        unknown_paths.extend(
+            condition_paths(condition)
+                .filter(|path| meta_data.query(path).is_none())
+                .map(|path| UnknownPath {
+                    path: path.to_string(),
+                    directive: name.to_string(),
+                    suggestions: suggest_paths(&meta_data, path),
+                    span,
+                    loc: locate(span),
+                }),
+        );
+    }
 
     // Evaluate directives and generate an remove/replace list
-    let mut remove_list = FxHashSet::default();
-    let mut replace_expr_list = Vec::new();
+    let mut if_directives = Vec::new();
+    let mut define_inline_directives = Vec::new();
+    let mut define_directives = Vec::new();
+    let mut replace_directives = Vec::new();
+    let mut if_next_directives = Vec::new();
+    let mut report_directives = Vec::new();
+    let mut inject_directives = Vec::new();
+    let mut define_inline_text_directives = Vec::new();
+    let mut rename_directives = Vec::new();
+    let mut import_swap_directives = Vec::new();
     for directive in directives {
         match directive {
-            Directive::If(if_directive) => {
-                if !meta_data.evaluate_bool(&if_directive.condition) {
-                    remove_list.insert(if_directive.range);
-                }
-            }
+            Directive::If(if_directive) => if_directives.push(if_directive),
             Directive::DefineInline(define_inline_directive) => {
-                let replacement = meta_data
-                    .query(&define_inline_directive.value)
-                    .map(|value| value.clone().to_ast())
-                    .or_else(|| define_inline_directive.default.map(|d| d.to_ast()))
-                    .expect("`value` or `default` is invalid");
-                replace_expr_list.push((define_inline_directive.pos, replacement));
+                define_inline_directives.push(define_inline_directive)
             }
+            Directive::Define(define_directive) => define_directives.push(define_directive),
+            Directive::Replace(replace_directive) => replace_directives.push(replace_directive),
+            Directive::IfNext(if_next_directive) => if_next_directives.push(if_next_directive),
+            Directive::Report(report_directive) => report_directives.push(report_directive),
+            Directive::Inject(inject_directive) => inject_directives.push(inject_directive),
+            Directive::DefineInlineText(define_inline_text_directive) => {
+                define_inline_text_directives.push(define_inline_text_directive)
+            }
+            Directive::Rename(rename_directive) => rename_directives.push(rename_directive),
+            Directive::ImportSwap(import_swap_directive) => {
+                import_swap_directives.push(import_swap_directive)
+            }
+        }
+    }
+
+    // Evaluate from the outermost region, so that a region nested in a removed region is ignored
+    // and `remove_list` ends up sorted and non-overlapping.
+    if_directives.sort_by_key(|d| (d.range.lo, Reverse(d.range.hi)));
+    let mut remove_list: Vec<Span> = Vec::new();
+    let mut remove_directive_list = Vec::new();
+    let mut keep_decl_list = Vec::new();
+    let mut null_args_list = Vec::new();
+    let mut report = TransformReport::default();
+    for if_directive in if_directives {
+        let range = if_directive.range;
+        if remove_list.last().is_some_and(|last| last.contains(range)) {
+            continue;
+        }
+        let result = evaluate(&if_directive.condition);
+        report.regions.push(RegionReport {
+            directive: if_directive.name.to_string(),
+            condition: if_directive.condition.clone(),
+            result,
+            kept: result != if_directive.negate,
+            span: range.into(),
+            loc: locate(if_directive.span),
+        });
+        if result == if_directive.negate {
+            if if_directive.keep_decl {
+                keep_decl_list.push(range);
+            }
+            if if_directive.null_args {
+                null_args_list.push(range);
+            }
+            merge_range(&mut remove_list, range);
+            remove_directive_list.push((range, if_directive.span, locate(if_directive.span)));
+        }
+    }
+
+    let mut remove_next_list: Vec<BytePos> = if_next_directives
+        .into_iter()
+        .filter(|d| !is_in_removed(&remove_list, d.pos))
+        .filter(|d| !evaluate(&d.condition))
+        .map(|d| d.pos)
+        .collect();
+    remove_next_list.sort();
+
+    for report_directive in report_directives {
+        if is_in_removed(&remove_list, report_directive.pos)
+            || !evaluate(&report_directive.condition)
+        {
+            continue;
+        }
+        if report_directive.is_error {
+            return Err(ConditionTransformError::Reported {
+                message: report_directive.message,
+                span: report_directive.span,
+                loc: locate(report_directive.span),
+            });
+        }
+        diagnostics.push(Diagnostic {
+            message: report_directive.message,
+            span: report_directive.span,
+            loc: locate(report_directive.span),
+        });
+    }
+
+    // Named inline values are resolved in document order into `inline.<name>`, which later
+    // directives look up before the metadata
+    let mut scratch = serde_json::json!({ "inline": {} });
+    let mut replace_expr_list = Vec::new();
+    let mut inline_const_list = Vec::new();
+    let mut replaced_positions = FxHashSet::default();
+    for define_inline_directive in define_inline_directives {
+        // Removal wins over replacement
+        if is_in_removed(&remove_list, define_inline_directive.pos) {
+            let span = define_inline_directive.span;
+            report.skipped.push(SkippedReport::inside_removed(
+                "define-inline",
+                span,
+                locate(span),
+            ));
+            continue;
+        }
+        // The first directive in document order wins on the same expression
+        if !define_inline_directive.as_const
+            && !replaced_positions.insert(define_inline_directive.pos)
+        {
+            let span = define_inline_directive.span;
+            diagnostics.push(Diagnostic {
+                message: "define-inline conflicts with an earlier define-inline on the same \
+                          expression and is ignored"
+                    .to_string(),
+                span,
+                loc: locate(span),
+            });
+            continue;
+        }
+        let resolved = scratch
+            .query(&define_inline_directive.value)
+            .or_else(|| meta_data.query(&define_inline_directive.value))
+            .cloned();
+        let default_used = resolved.is_none();
+        let value = resolved.or_else(|| {
+            define_inline_directive
+                .default
+                .map(serde_json::Value::String)
+        });
+        let Some(value) = value else {
+            unknown_paths.push(UnknownPath {
+                path: define_inline_directive.value.clone(),
+                directive: "define-inline".to_string(),
+                suggestions: suggest_paths(&meta_data, &define_inline_directive.value),
+                span: define_inline_directive.span,
+                loc: locate(define_inline_directive.span),
+            });
+            diagnostics.push(Diagnostic {
+                message: format!(
+                    "define-inline has no value at `{}` and no default",
+                    define_inline_directive.value
+                ),
+                span: define_inline_directive.span,
+                loc: locate(define_inline_directive.span),
+            });
+            continue;
+        };
+        if let Some(name) = &define_inline_directive.name {
+            scratch["inline"][name] = value.clone();
+        }
+        report.inlines.push(InlineReport {
+            path: define_inline_directive.value,
+            value: value.clone(),
+            default_used,
+            span: define_inline_directive.span.into(),
+            loc: locate(define_inline_directive.span),
+        });
+        let expr = if define_inline_directive.undefined_for_null {
+            value.to_ast_with_undefined_for_null()
+        } else {
+            value.to_ast()
+        };
+        match define_inline_directive.name {
+            Some(name) if define_inline_directive.as_const => inline_const_list.push((
+                define_inline_directive.pos,
+                InlineConst {
+                    name,
+                    init: expr,
+                    span: define_inline_directive.span,
+                    loc: locate(define_inline_directive.span),
+                },
+            )),
+            _ => replace_expr_list.push((define_inline_directive.pos, expr)),
+        }
+    }
+
+    let mut replace_text_list = Vec::new();
+    for define_inline_text_directive in define_inline_text_directives {
+        if is_in_removed(&remove_list, define_inline_text_directive.pos) {
+            let span = define_inline_text_directive.span;
+            report.skipped.push(SkippedReport::inside_removed(
+                "define-inline-text",
+                span,
+                locate(span),
+            ));
+            continue;
+        }
+        // Strings are inlined as is, other values as their JSON text
+        let text = scratch
+            .query(&define_inline_text_directive.value)
+            .or_else(|| meta_data.query(&define_inline_text_directive.value))
+            .map(|value| match value {
+                serde_json::Value::String(s) => s.clone(),
+                value => value.to_string(),
+            })
+            .or(define_inline_text_directive.default);
+        let Some(text) = text else {
+            diagnostics.push(Diagnostic {
+                message: format!(
+                    "define-inline-text has no value at `{}` and no default",
+                    define_inline_text_directive.value
+                ),
+                span: define_inline_text_directive.span,
+                loc: locate(define_inline_text_directive.span),
+            });
+            continue;
+        };
+        replace_text_list.push((
+            define_inline_text_directive.pos,
+            TextReplacer {
+                placeholder: define_inline_text_directive.placeholder,
+                text,
+            },
+        ));
+    }
+    replace_text_list.sort_by_key(|(pos, _)| *pos);
+
+    let mut swap_next_list = Vec::new();
+    let mut swap_module_list = Vec::new();
+    let mut swapped_specifiers = FxHashSet::default();
+    for import_swap_directive in import_swap_directives {
+        if is_in_removed(&remove_list, import_swap_directive.pos) {
+            let span = import_swap_directive.span;
+            report.skipped.push(SkippedReport::inside_removed(
+                "import-swap",
+                span,
+                locate(span),
+            ));
+            continue;
+        }
+        if !evaluate(&import_swap_directive.condition) {
+            continue;
+        }
+        // A specifier is only swapped once, by the first directive in document order
+        let scope = (!import_swap_directive.module_scope).then_some(import_swap_directive.pos);
+        if !swapped_specifiers.insert((scope, import_swap_directive.from.clone())) {
+            let span = import_swap_directive.span;
+            diagnostics.push(Diagnostic {
+                message: format!(
+                    "import-swap of `{}` conflicts with an earlier import-swap and is ignored",
+                    import_swap_directive.from
+                ),
+                span,
+                loc: locate(span),
+            });
+            continue;
+        }
+        let swapper = ImportSwapper {
+            from: import_swap_directive.from,
+            to: import_swap_directive.to,
+        };
+        match import_swap_directive.module_scope {
+            true => swap_module_list.push(swapper),
+            false => swap_next_list.push((import_swap_directive.pos, swapper)),
+        }
+    }
+    swap_next_list.sort_by_key(|(pos, _)| *pos);
+
+    let mut defined_names = FxHashSet::default();
+    let mut insert_list = Vec::new();
+    for define_directive in define_directives {
+        if !defined_names.insert(define_directive.name.clone()) {
+            return Err(ConditionTransformError::DuplicateDefine {
+                name: define_directive.name,
+                span: define_directive.span,
+                loc: locate(define_directive.span),
+            });
+        }
+        if is_in_removed(&remove_list, define_directive.pos) {
+            let span = define_directive.span;
+            report
+                .skipped
+                .push(SkippedReport::inside_removed("define", span, locate(span)));
+            continue;
+        }
+        let init = meta_data
+            .query(&define_directive.value)
+            .map(|value| value.clone().to_ast())
+            .or_else(|| define_directive.default.map(|d| d.to_ast()));
+        let Some(init) = init else {
+            diagnostics.push(Diagnostic {
+                message: format!(
+                    "define `{}` has no value at `{}` and no default",
+                    define_directive.name, define_directive.value
+                ),
+                span: define_directive.span,
+                loc: locate(define_directive.span),
+            });
+            continue;
+        };
+        insert_list.push((
+            define_directive.pos,
+            vec![const_decl(define_directive.name, init)],
+        ));
+    }
+    for inject_directive in inject_directives {
+        if is_in_removed(&remove_list, inject_directive.pos) {
+            let span = inject_directive.span;
+            report
+                .skipped
+                .push(SkippedReport::inside_removed("inject", span, locate(span)));
+            continue;
+        }
+        if !evaluate(&inject_directive.condition) {
+            continue;
+        }
+        // The snippet is parsed without comments, so macros in it are not processed
+        let snippet = meta_data
+            .query(&inject_directive.code)
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| format!("inject has no snippet at `{}`", inject_directive.code))
+            .and_then(|code| {
+                parse_snippet(code).map_err(|err| {
+                    format!(
+                        "inject snippet at `{}` failed to parse: {err}",
+                        inject_directive.code
+                    )
+                })
+            });
+        match snippet {
+            Ok(stmts) => insert_list.push((inject_directive.pos, stmts)),
+            Err(message) => diagnostics.push(Diagnostic {
+                message,
+                span: inject_directive.span,
+                loc: locate(inject_directive.span),
+            }),
+        }
+    }
+    insert_list.sort_by_key(|(pos, _)| *pos);
+
+    let mut replace_region_list = Vec::new();
+    for replace_directive in replace_directives {
+        if is_in_removed(&remove_list, replace_directive.range.lo) {
+            let span = replace_directive.span;
+            report
+                .skipped
+                .push(SkippedReport::inside_removed("replace", span, locate(span)));
+            continue;
+        }
+        let snippet = meta_data
+            .query(&replace_directive.with)
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| format!("replace has no snippet at `{}`", replace_directive.with))
+            .and_then(|code| {
+                parse_snippet(code).map_err(|err| {
+                    format!(
+                        "replace snippet at `{}` failed to parse: {err}",
+                        replace_directive.with
+                    )
+                })
+            });
+        match snippet {
+            Ok(stmts) => replace_region_list.push((replace_directive.range, stmts)),
+            Err(message) => diagnostics.push(Diagnostic {
+                message,
+                span: replace_directive.span,
+                loc: locate(replace_directive.span),
+            }),
+        }
+    }
+
+    let mut rename_list = Vec::new();
+    let mut renamed_names = FxHashSet::default();
+    for rename_directive in rename_directives {
+        let span = rename_directive.span;
+        if is_in_removed(&remove_list, rename_directive.pos) {
+            report
+                .skipped
+                .push(SkippedReport::inside_removed("rename", span, locate(span)));
+            continue;
+        }
+        if !renamed_names.insert(rename_directive.from.clone()) {
+            diagnostics.push(Diagnostic {
+                message: format!(
+                    "rename `{}` conflicts with an earlier rename of it and is ignored",
+                    rename_directive.from
+                ),
+                span,
+                loc: locate(span),
+            });
+            continue;
         }
+        let Some(to) = meta_data.query(&rename_directive.to) else {
+            unknown_paths.push(UnknownPath {
+                path: rename_directive.to.clone(),
+                directive: "rename".to_string(),
+                suggestions: suggest_paths(&meta_data, &rename_directive.to),
+                span,
+                loc: locate(span),
+            });
+            diagnostics.push(Diagnostic {
+                message: format!("rename has no name at `{}`", rename_directive.to),
+                span,
+                loc: locate(span),
+            });
+            continue;
+        };
+        let to = to
+            .as_str()
+            .ok_or_else(|| format!("rename name at `{}` is not a string", rename_directive.to))
+            .and_then(|to| {
+                Ident::verify_symbol(to)
+                    .map(|_| to)
+                    .map_err(|_| format!("rename name `{to}` is not a valid identifier"))
+            });
+        match to {
+            Ok(to) => rename_list.push(Rename {
+                from: Atom::from(rename_directive.from),
+                to: Atom::from(to),
+                span,
+                loc: locate(span),
+            }),
+            Err(message) => diagnostics.push(Diagnostic {
+                message,
+                span,
+                loc: locate(span),
+            }),
+        }
+    }
+
+    // Stable, so the first directive wins on the same position
+    replace_expr_list.sort_by_key(|(pos, _)| *pos);
+    inline_const_list.sort_by_key(|(pos, _)| *pos);
+
+    unknown_paths.sort_by_key(|path| path.span.lo);
+    if options.strict && !unknown_paths.is_empty() {
+        return Err(ConditionTransformError::UnknownPaths {
+            paths: unknown_paths,
+        });
     }
+    report.unknown_paths = unknown_paths.iter().map(UnknownPathReport::from).collect();
+    report.diagnostics = diagnostics.iter().map(DiagnosticReport::from).collect();
 
-    visit_mut_pass(RemoveReplaceTransformer {
+    let diagnostics = Diagnostics::new(diagnostics);
+    let report = TransformReportHandle::new(report);
+    let transformer = RemoveReplaceTransformer {
         remove_list,
+        remove_directive_list,
         replace_expr_list,
-    })
+        insert_list,
+        inline_const_list,
+        replace_region_list,
+        remove_next_list,
+        keep_decl_list,
+        null_args_list,
+        replace_text_list,
+        swap_next_list,
+        swap_module_list,
+        rename_list,
+        removed_expr: options.removed_expr.to_expr(),
+        removed_undefined: matches!(options.removed_expr, RemovedExprReplacement::Undefined),
+        diagnostics: diagnostics.clone(),
+        report: report.clone(),
+    };
+    Ok((visit_mut_pass(transformer), diagnostics, report))
+}
+
+fn const_decl(name: String, init: Expr) -> Stmt {
+    Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        kind: VarDeclKind::Const,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(BindingIdent::from(Ident::new_no_ctxt(
+                name.into(),
+                DUMMY_SP,
+            ))),
+            init: Some(Box::new(init)),
+            definite: false,
+        }],
+    })))
+}
+
+/// The declaration of an export without the export, if it declares a name
+fn unexport(decl: ModuleDecl) -> Option<Decl> {
+    match decl {
+        ModuleDecl::ExportDecl(export) => Some(export.decl),
+        ModuleDecl::ExportDefaultDecl(export) => match export.decl {
+            DefaultDecl::Fn(FnExpr {
+                ident: Some(ident),
+                function,
+            }) => Some(Decl::Fn(FnDecl {
+                ident,
+                declare: false,
+                function,
+            })),
+            DefaultDecl::Class(ClassExpr {
+                ident: Some(ident),
+                class,
+            }) => Some(Decl::Class(ClassDecl {
+                ident,
+                declare: false,
+                class,
+            })),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The empty statement a removed statement is replaced with. It is only needed where a single
+/// statement is expected, e.g. the body of `while`, and is dropped from statement lists.
+/// Empty statements written in the source have a span, so they are kept.
+fn is_removal_leftover(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::Empty(empty) if empty.span.is_dummy())
+}
+
+/// A variable declaration whose declarators have all been removed
+fn is_empty_var(decl: &Decl) -> bool {
+    matches!(decl, Decl::Var(var) if var.decls.is_empty())
+}
+
+/// A node spliced in from a snippet, which has no position in the source. The scans for the first
+/// node after a position skip it, as it would end the scanned range before it starts.
+fn is_inserted<T: Spanned>(item: &T) -> bool {
+    item.span().is_dummy()
+}
+
+/// Apply the visitors of `list` to the first node of `items` after their positions
+fn visit_next_with<T: Spanned + VisitMutWith<V>, V: VisitMut>(
+    list: &mut Vec<(BytePos, V)>,
+    items: &mut [T],
+) {
+    if list.is_empty() {
+        return;
+    }
+
+    let mut prev_hi = BytePos(0);
+    for item in items.iter_mut() {
+        if is_inserted(item) {
+            continue;
+        }
+        let (lo, hi) = (item.span_lo(), item.span_hi());
+        let start = list.partition_point(|(pos, _)| *pos < prev_hi);
+        let end = list.partition_point(|(pos, _)| *pos <= lo);
+        prev_hi = prev_hi.max(hi);
+        for (_, mut visitor) in list.drain(start..end) {
+            item.visit_mut_with(&mut visitor);
+        }
+    }
+}
+
+/// Add `range` to the sorted and non-overlapping `ranges`, which it doesn't start before the last
+/// of. It is merged into the last range when they overlap or touch.
+fn merge_range(ranges: &mut Vec<Span>, range: Span) {
+    match ranges.last_mut() {
+        Some(last) if last.hi >= range.lo => last.hi = last.hi.max(range.hi),
+        _ => ranges.push(range),
+    }
+}
+
+fn is_in_removed(remove_list: &[Span], pos: BytePos) -> bool {
+    let i = remove_list.partition_point(|range| range.lo <= pos);
+    i > 0 && pos < remove_list[i - 1].hi
+}
+
+/// Whether one of the sorted and non-overlapping `ranges` contains `span`.
+/// Only the last range starting before `span` can contain it.
+fn in_ranges(ranges: &[Span], span: Span) -> bool {
+    let i = ranges.partition_point(|range| range.lo <= span.lo);
+    i > 0 && ranges[i - 1].contains(span)
+}
+
+fn required_attr(
+    macro_node: &MacroNode,
+    attr: &'static str,
+    locate: impl Fn(Span) -> Option<LineCol>,
+) -> Result<String, ConditionTransformError> {
+    macro_node
+        .attrs
+        .get(attr)
+        .cloned()
+        .ok_or_else(|| ConditionTransformError::MissingAttr {
+            directive: macro_node.directive.clone(),
+            attr,
+            span: macro_node.span,
+            loc: locate(macro_node.span),
+        })
 }
 
 /// Remove or replace the ast nodes by traversing the ast.
-/// We only focus on three types of ast: `ModuleItem`, `Stmt` and `Expr`, which covers most use cases.
+/// We mainly focus on three types of ast: `ModuleItem`, `Stmt` and `Expr`, which covers most use cases.
+/// Object properties, class members, switch cases, array elements, call arguments and
+/// import/export specifiers are dropped as a whole, so that no `key: null` is left behind.
 pub struct RemoveReplaceTransformer {
-    /// `remove_list` contains a set of ranges.
+    /// `remove_list` contains sorted and non-overlapping ranges.
     /// If a visited ast is in one of the ranges, it will be removed.
-    remove_list: FxHashSet<Span>,
-    /// `replace_expr_list` contains a position and a replacement.
+    remove_list: Vec<Span>,
+    /// `remove_directive_list` contains the removed ranges sorted by their start, with the span and
+    /// location of their directive, so that a diagnostic about a removal points at the directive.
+    remove_directive_list: Vec<(Span, Span, Option<LineCol>)>,
+    /// `replace_expr_list` contains positions sorted ascending and their replacement.
     /// If the start of an ast node is on the position, it will be replaced.
     replace_expr_list: Vec<(BytePos, Expr)>,
+    /// `insert_list` contains positions sorted ascending and the statements to insert there.
+    /// The statements are spliced into the statement list right before the first node after the position.
+    insert_list: Vec<(BytePos, Vec<Stmt>)>,
+    /// `inline_const_list` contains positions sorted ascending and the constants declared there,
+    /// which are inserted like `insert_list` unless the statement list already declares the name.
+    inline_const_list: Vec<(BytePos, InlineConst)>,
+    /// `replace_region_list` contains ranges and their replacement statements.
+    /// The first statement in a range is replaced with the statements, and the rest are removed.
+    replace_region_list: Vec<(Span, Vec<Stmt>)>,
+    /// `remove_next_list` contains positions sorted ascending.
+    /// The first statement starting after a position is removed, which is a whole declaration
+    /// with all its declarators, or a whole import.
+    remove_next_list: Vec<BytePos>,
+    /// `keep_decl_list` contains the sorted and non-overlapping removed ranges with the `keep-decl` attr.
+    /// An export in one of the ranges is replaced with its declaration rather than removed.
+    keep_decl_list: Vec<Span>,
+    /// `null_args_list` contains the sorted and non-overlapping removed ranges with `mode="null"`.
+    /// A call argument in one of the ranges is replaced with `removed_expr` rather than dropped.
+    null_args_list: Vec<Span>,
+    /// `replace_text_list` contains positions sorted ascending and the placeholder to substitute.
+    /// The placeholder is substituted in the first statement starting after a position.
+    replace_text_list: Vec<(BytePos, TextReplacer)>,
+    /// `swap_next_list` contains positions sorted ascending and the module specifier to swap.
+    /// The specifier is swapped in the first statement starting after a position.
+    swap_next_list: Vec<(BytePos, ImportSwapper)>,
+    /// `swap_module_list` contains the module specifiers to swap in the whole program.
+    swap_module_list: Vec<ImportSwapper>,
+    /// `rename_list` contains the top-level bindings to rename once the other directives are applied.
+    rename_list: Vec<Rename>,
+    /// `removed_expr` replaces the expressions in removed ranges that can't be dropped.
+    removed_expr: Expr,
+    /// With `RemovedExprReplacement::Undefined`, a removed arrow body or return argument is dropped
+    /// rather than replaced, leaving `() => {}` or `return;`.
+    removed_undefined: bool,
+    diagnostics: Diagnostics,
+    report: TransformReportHandle,
+}
+
+/// A constant declared by `define-inline` with `as="const"`
+pub struct InlineConst {
+    name: String,
+    init: Expr,
+    span: Span,
+    loc: Option<LineCol>,
+}
+
+impl RemoveReplaceTransformer {
+    /// Record a diagnostic found while the pass runs, in the report too
+    fn diagnose(&self, diagnostic: Diagnostic) {
+        self.report.push_diagnostic(&diagnostic);
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn is_removed(&self, span: Span) -> bool {
+        in_ranges(&self.remove_list, span)
+    }
+
+    /// The span and location of the directive whose range covers `span`
+    fn removing_directive(&self, span: Span) -> (Span, Option<LineCol>) {
+        self.remove_directive_list
+            .iter()
+            .find(|(range, ..)| range.contains(span))
+            .map_or((span, None), |(_, directive, loc)| (*directive, *loc))
+    }
+
+    fn is_decl_kept(&self, span: Span) -> bool {
+        in_ranges(&self.keep_decl_list, span)
+    }
+
+    /// The replacement at the first position of `replace_expr_list` in `lo..=hi`
+    fn replacement_within(&self, lo: BytePos, hi: BytePos) -> Option<&Expr> {
+        let i = self.replace_expr_list.partition_point(|(pos, _)| *pos < lo);
+        self.replace_expr_list
+            .get(i)
+            .filter(|(pos, _)| *pos <= hi)
+            .map(|(_, replacement)| replacement)
+    }
+
+    /// Swap the nodes of `items` lying in a replace region for the region's statements
+    fn replace_regions<T: Spanned>(&mut self, items: &mut Vec<T>, wrap: impl Fn(Stmt) -> T) {
+        if self.replace_region_list.is_empty() {
+            return;
+        }
+
+        let mut replaced = Vec::with_capacity(items.len());
+        for item in items.drain(..) {
+            let region = self
+                .replace_region_list
+                .iter_mut()
+                .find(|(range, _)| range.contains(item.span()));
+            match region {
+                // Only the first node takes the statements, the rest are left out
+                Some((_, stmts)) => replaced.extend(std::mem::take(stmts).into_iter().map(&wrap)),
+                None => replaced.push(item),
+            }
+        }
+        *items = replaced;
+    }
+
+    /// Drop the import and export specifiers in removed ranges, and the whole declaration once
+    /// none is left. Imports without any specifier are only run for their side effects, so they are kept.
+    fn remove_specifiers(&self, items: &mut Vec<ModuleItem>) {
+        let mut removed = 0;
+        items.retain_mut(|item| {
+            // A declaration removed as a whole is left to `visit_mut_module_item`
+            if self.is_removed(item.span()) {
+                return true;
+            }
+            let (len, left) = match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(import))
+                    if !import.specifiers.is_empty() =>
+                {
+                    let len = import.specifiers.len();
+                    import
+                        .specifiers
+                        .retain(|specifier| !self.is_removed(specifier.span()));
+                    (len, import.specifiers.len())
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export))
+                    if !export.specifiers.is_empty() =>
+                {
+                    let len = export.specifiers.len();
+                    export
+                        .specifiers
+                        .retain(|specifier| !self.is_removed(specifier.span()));
+                    (len, export.specifiers.len())
+                }
+                _ => return true,
+            };
+            removed += len - left;
+            // A declaration left without any specifier goes as a whole
+            if left == 0 {
+                self.report.count_removed(|counts| counts.module_items += 1);
+            }
+            left > 0
+        });
+        self.report
+            .count_removed(|counts| counts.specifiers += removed);
+    }
+
+    /// Drop the first node of `items` after each pending position of `remove_next_list`,
+    /// returning the number of dropped nodes
+    fn remove_next<T: Spanned>(&mut self, items: &mut Vec<T>) -> usize {
+        if self.remove_next_list.is_empty() {
+            return 0;
+        }
+
+        let len = items.len();
+        let mut prev_hi = BytePos(0);
+        items.retain(|item| {
+            if is_inserted(item) {
+                return true;
+            }
+            let (lo, hi) = (item.span_lo(), item.span_hi());
+            let start = self.remove_next_list.partition_point(|pos| *pos < prev_hi);
+            let end = self.remove_next_list.partition_point(|pos| *pos <= lo);
+            prev_hi = prev_hi.max(hi);
+            if start == end {
+                return true;
+            }
+            self.remove_next_list.drain(start..end);
+            false
+        });
+        len - items.len()
+    }
+
+    /// Substitute the placeholders and swap the module specifiers in the first node of `items`
+    /// after each pending position of `replace_text_list` and `swap_next_list`
+    fn visit_next<T>(&mut self, items: &mut [T])
+    where
+        T: Spanned + VisitMutWith<TextReplacer> + VisitMutWith<ImportSwapper>,
+    {
+        visit_next_with(&mut self.replace_text_list, items);
+        visit_next_with(&mut self.swap_next_list, items);
+    }
+
+    /// Splice the pending insertions positioned between two sibling nodes of `items`.
+    /// The insertions inside a node are left to the nested statement lists, which are visited later.
+    fn splice_insertions<T: Spanned + VisitWith<DeclaredNames>>(
+        &mut self,
+        items: &mut Vec<T>,
+        wrap: impl Fn(Stmt) -> T,
+    ) {
+        if self.insert_list.is_empty() && self.inline_const_list.is_empty() {
+            return;
+        }
+
+        let declared = self.declared_names(items);
+        let mut spliced = Vec::with_capacity(items.len());
+        let mut prev_hi = BytePos(0);
+        for item in items.drain(..) {
+            if is_inserted(&item) {
+                spliced.push(item);
+                continue;
+            }
+            let insertions = self.take_insertions(prev_hi, item.span_lo(), &declared);
+            spliced.extend(insertions.map(&wrap));
+            prev_hi = prev_hi.max(item.span_hi());
+            spliced.push(item);
+        }
+        *items = spliced;
+    }
+
+    /// Append the insertions positioned after the last node of a program body
+    fn append_insertions<T: Spanned + VisitWith<DeclaredNames>>(
+        &mut self,
+        items: &mut Vec<T>,
+        wrap: impl Fn(Stmt) -> T,
+    ) {
+        let last_hi = items
+            .iter()
+            .map(|item| item.span_hi())
+            .max()
+            .unwrap_or(BytePos(0));
+        let declared = self.declared_names(items);
+        let rest = self.take_insertions(last_hi, BytePos(u32::MAX), &declared);
+        items.extend(rest.map(wrap));
+    }
+
+    /// Rename the top-level bindings of `rename_list` in a program body. A rename is skipped when
+    /// the binding is missing, or when the new name would collide with another binding or with a
+    /// reference to a global of that name.
+    fn rename_bindings<T: Clone + VisitWith<DeclaredNames> + VisitMutWith<ScopedRenamer>>(
+        &mut self,
+        items: &mut Vec<T>,
+    ) {
+        for rename in std::mem::take(&mut self.rename_list) {
+            let mut declared = DeclaredNames::default();
+            for item in items.iter() {
+                item.visit_with(&mut declared);
+            }
+            let problem = if !declared.0.contains(&rename.from) {
+                Some("has no top-level binding")
+            } else if declared.0.contains(&rename.to) {
+                Some("conflicts with an existing binding")
+            } else {
+                // Renamed on a copy, as a reference captured by a nested binding of the new name
+                // is only found on the way
+                let mut renamer = ScopedRenamer::new(rename.from.clone(), rename.to.clone());
+                let mut renamed = items.clone();
+                for item in &mut renamed {
+                    item.visit_mut_with(&mut renamer);
+                }
+                if renamer.captured {
+                    Some("would be captured by a nested binding")
+                } else if renamer.referenced {
+                    Some("would capture the existing references to the new name")
+                } else {
+                    *items = renamed;
+                    None
+                }
+            };
+            if let Some(problem) = problem {
+                self.diagnose(Diagnostic {
+                    message: format!(
+                        "rename `{}` to `{}` {problem} and is ignored",
+                        rename.from, rename.to
+                    ),
+                    span: rename.span,
+                    loc: rename.loc,
+                });
+            }
+        }
+    }
+
+    /// The names declared in `items`, only collected when there is a constant to check against
+    fn declared_names<T: VisitWith<DeclaredNames>>(&self, items: &[T]) -> FxHashSet<Atom> {
+        let mut declared = DeclaredNames::default();
+        if !self.inline_const_list.is_empty() {
+            for item in items {
+                item.visit_with(&mut declared);
+            }
+        }
+        declared.0
+    }
+
+    fn take_insertions(
+        &mut self,
+        lo: BytePos,
+        hi: BytePos,
+        declared: &FxHashSet<Atom>,
+    ) -> std::vec::IntoIter<Stmt> {
+        let start = self.inline_const_list.partition_point(|(pos, _)| *pos < lo);
+        let end = self
+            .inline_const_list
+            .partition_point(|(pos, _)| *pos <= hi);
+        let inline_consts: Vec<_> = self.inline_const_list.drain(start..end).collect();
+        let mut insertions = Vec::new();
+        for (_, inline_const) in inline_consts {
+            if declared.contains(&Atom::from(&*inline_const.name)) {
+                self.diagnose(Diagnostic {
+                    message: format!(
+                        "define-inline `{}` conflicts with an existing binding and is ignored",
+                        inline_const.name
+                    ),
+                    span: inline_const.span,
+                    loc: inline_const.loc,
+                });
+                continue;
+            }
+            insertions.push(const_decl(inline_const.name, inline_const.init));
+        }
+
+        let start = self.insert_list.partition_point(|(pos, _)| *pos < lo);
+        let end = self.insert_list.partition_point(|(pos, _)| *pos <= hi);
+        insertions.extend(
+            self.insert_list
+                .drain(start..end)
+                .flat_map(|(_, stmts)| stmts),
+        );
+        insertions.into_iter()
+    }
 }
 
 impl VisitMut for RemoveReplaceTransformer {
+    fn visit_mut_module(&mut self, node: &mut Module) {
+        node.visit_mut_children_with(self);
+        self.append_insertions(&mut node.body, ModuleItem::Stmt);
+        for mut swapper in self.swap_module_list.drain(..) {
+            node.visit_mut_with(&mut swapper);
+        }
+        self.rename_bindings(&mut node.body);
+    }
+
+    fn visit_mut_script(&mut self, node: &mut Script) {
+        node.visit_mut_children_with(self);
+        self.append_insertions(&mut node.body, |stmt| stmt);
+        for mut swapper in self.swap_module_list.drain(..) {
+            node.visit_mut_with(&mut swapper);
+        }
+        self.rename_bindings(&mut node.body);
+    }
+
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        self.replace_regions(items, ModuleItem::Stmt);
+        let removed = self.remove_next(items);
+        self.report
+            .count_removed(|counts| counts.module_items += removed);
+        self.remove_specifiers(items);
+        self.visit_next(items);
+        self.splice_insertions(items, ModuleItem::Stmt);
+        items.visit_mut_children_with(self);
+        items.retain(|item| !matches!(item, ModuleItem::Stmt(stmt) if is_removal_leftover(stmt)));
+    }
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        self.replace_regions(stmts, |stmt| stmt);
+        let removed = self.remove_next(stmts);
+        self.report.count_removed(|counts| counts.stmts += removed);
+        self.visit_next(stmts);
+        self.splice_insertions(stmts, |stmt| stmt);
+        stmts.visit_mut_children_with(self);
+        stmts.retain(|stmt| !is_removal_leftover(stmt));
+    }
+
     fn visit_mut_module_item(&mut self, node: &mut ModuleItem) {
         // Check if this node should be removed
-        for remove in self.remove_list.iter() {
-            if remove.contains(node.span()) {
-                // Replace with an empty export statement instead of invalid token
-                *node = ModuleItem::Stmt(Stmt::Empty(swc_core::ecma::ast::EmptyStmt {
+        if self.is_removed(node.span()) {
+            // Replace with an empty export statement instead of invalid token
+            let removed = std::mem::replace(
+                node,
+                ModuleItem::Stmt(Stmt::Empty(swc_core::ecma::ast::EmptyStmt {
                     span: swc_core::common::DUMMY_SP,
-                }));
+                })),
+            );
+            if self.is_decl_kept(removed.span())
+                && let ModuleItem::ModuleDecl(decl) = removed
+                && let Some(decl) = unexport(decl)
+            {
+                *node = ModuleItem::Stmt(Stmt::Decl(decl));
                 return;
             }
+            self.report.count_removed(|counts| counts.module_items += 1);
+            return;
+        }
+
+        node.visit_mut_children_with(self);
+
+        if let ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) = node
+            && is_empty_var(&export.decl)
+        {
+            *node = ModuleItem::Stmt(Stmt::Empty(swc_core::ecma::ast::EmptyStmt {
+                span: swc_core::common::DUMMY_SP,
+            }));
+            self.report.count_removed(|counts| counts.module_items += 1);
         }
+    }
+
+    fn visit_mut_var_decl(&mut self, node: &mut VarDecl) {
+        // Drop the declarator along with its initializer, the statement goes if none is left
+        let len = node.decls.len();
+        node.decls.retain(|decl| !self.is_removed(decl.span()));
+        let removed = len - node.decls.len();
+        self.report
+            .count_removed(|counts| counts.declarators += removed);
 
         node.visit_mut_children_with(self);
     }
 
+    fn visit_mut_for_head(&mut self, node: &mut ForHead) {
+        // A for-in/of head declares exactly one binding and can't be left without it, so it is
+        // kept as written
+        if let ForHead::VarDecl(var_decl) = node
+            && let Some(decl) = var_decl
+                .decls
+                .iter()
+                .find(|decl| self.is_removed(decl.span()))
+        {
+            let (span, loc) = self.removing_directive(decl.span());
+            self.diagnose(Diagnostic {
+                message: "the declarator of a for-in/of head can't be removed and is kept"
+                    .to_string(),
+                span,
+                loc,
+            });
+            return;
+        }
+
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_prop_or_spreads(&mut self, props: &mut Vec<PropOrSpread>) {
+        // Drop the whole property rather than nulling its value
+        let len = props.len();
+        props.retain(|prop| !self.is_removed(prop.span()));
+        let removed = len - props.len();
+        self.report.count_removed(|counts| counts.props += removed);
+
+        props.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_class_members(&mut self, members: &mut Vec<ClassMember>) {
+        // Decorators are part of the member, so they go with it
+        let len = members.len();
+        members.retain(|member| !self.is_removed(member.span()));
+        let removed = len - members.len();
+        self.report
+            .count_removed(|counts| counts.class_members += removed);
+
+        members.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_switch_cases(&mut self, cases: &mut Vec<SwitchCase>) {
+        // Drop the whole case with its statements rather than leaving an empty `case`
+        let len = cases.len();
+        cases.retain(|case| !self.is_removed(case.span()));
+        let removed = len - cases.len();
+        self.report
+            .count_removed(|counts| counts.switch_cases += removed);
+
+        cases.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_opt_vec_expr_or_spreads(&mut self, elems: &mut Vec<Option<ExprOrSpread>>) {
+        // Drop array elements rather than nulling them, holes written in the source are kept
+        let len = elems.len();
+        elems.retain(|elem| {
+            elem.as_ref()
+                .is_none_or(|elem| !self.is_removed(elem.span()))
+        });
+        let removed = len - elems.len();
+        self.report
+            .count_removed(|counts| counts.array_elements += removed);
+
+        elems.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_expr_or_spreads(&mut self, args: &mut Vec<ExprOrSpread>) {
+        // Drop call arguments rather than passing null, unless the positions matter
+        let len = args.len();
+        args.retain_mut(|arg| {
+            if !self.is_removed(arg.span()) {
+                return true;
+            }
+            if !in_ranges(&self.null_args_list, arg.span()) {
+                return false;
+            }
+            // The expression is replaced when visited, and `...null` would throw
+            arg.spread = None;
+            true
+        });
+        let removed = len - args.len();
+        self.report
+            .count_removed(|counts| counts.call_args += removed);
+
+        args.visit_mut_children_with(self);
+    }
+
     fn visit_mut_stmt(&mut self, node: &mut Stmt) {
         // Check if this statement should be removed
-        for remove in self.remove_list.iter() {
-            if remove.contains(node.span()) {
-                // Create an empty statement instead of invalid token
+        if self.is_removed(node.span()) {
+            // Create an empty statement instead of invalid token
+            *node = Stmt::Empty(swc_core::ecma::ast::EmptyStmt {
+                span: swc_core::common::DUMMY_SP,
+            });
+            self.report.count_removed(|counts| counts.stmts += 1);
+            return;
+        }
+
+        node.visit_mut_children_with(self);
+
+        match node {
+            Stmt::Decl(decl) if is_empty_var(decl) => {
                 *node = Stmt::Empty(swc_core::ecma::ast::EmptyStmt {
                     span: swc_core::common::DUMMY_SP,
                 });
-                return;
+                self.report.count_removed(|counts| counts.stmts += 1);
+            }
+            Stmt::For(for_stmt) if matches!(&for_stmt.init, Some(VarDeclOrExpr::VarDecl(var)) if var.decls.is_empty()) =>
+            {
+                for_stmt.init = None;
             }
+            _ => {}
+        }
+    }
+
+    fn visit_mut_tpl(&mut self, tpl: &mut Tpl) {
+        // A comment right after `${` is attached to the end of `${` rather than to the expression
+        for (quasi, expr) in tpl.quasis.iter().zip(tpl.exprs.iter_mut()) {
+            if let Some(replacement) = self.replacement_within(quasi.span.hi, expr.span_lo()) {
+                **expr = replacement.clone();
+            }
+        }
+
+        tpl.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_arrow_expr(&mut self, node: &mut ArrowExpr) {
+        if self.removed_undefined
+            && let BlockStmtOrExpr::Expr(body) = &*node.body
+            && self.is_removed(body.unwrap_parens().span())
+        {
+            node.params.visit_mut_with(self);
+            *node.body = BlockStmtOrExpr::BlockStmt(BlockStmt {
+                span: DUMMY_SP,
+                ctxt: SyntaxContext::empty(),
+                stmts: Vec::new(),
+            });
+            self.report.count_removed(|counts| counts.exprs += 1);
+            return;
+        }
+
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_return_stmt(&mut self, node: &mut ReturnStmt) {
+        if self.removed_undefined
+            && node
+                .arg
+                .as_ref()
+                .is_some_and(|arg| self.is_removed(arg.unwrap_parens().span()))
+        {
+            node.arg = None;
+            self.report.count_removed(|counts| counts.exprs += 1);
+            return;
         }
 
         node.visit_mut_children_with(self);
@@ -126,24 +1409,91 @@ impl VisitMut for RemoveReplaceTransformer {
 
     fn visit_mut_expr(&mut self, node: &mut Expr) {
         // Check if this expression should be replaced first
-        for (pos, replacement) in self.replace_expr_list.iter() {
-            if node.span_lo() == *pos {
-                *node = replacement.clone();
-                return;
-            }
+        if let Some(replacement) = self.replacement_within(node.span_lo(), node.span_lo()) {
+            *node = replacement.clone();
+            return;
         }
 
         // Check if this expression should be removed
-        for remove in self.remove_list.iter() {
-            if remove.contains(node.span()) {
-                // Replace with a null literal instead of invalid token
-                *node = Expr::Lit(swc_core::ecma::ast::Lit::Null(swc_core::ecma::ast::Null {
-                    span: swc_core::common::DUMMY_SP,
-                }));
-                return;
-            }
+        if self.is_removed(node.span()) {
+            // Replace with a literal like null instead of invalid token
+            *node = self.removed_expr.clone();
+            self.report.count_removed(|counts| counts.exprs += 1);
+            return;
         }
 
         node.visit_mut_children_with(self);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(lo: u32, hi: u32) -> Span {
+        Span::new(BytePos(lo), BytePos(hi))
+    }
+
+    fn merged(ranges: &[(u32, u32)]) -> Vec<Span> {
+        let mut merged = Vec::new();
+        for &(lo, hi) in ranges {
+            merge_range(&mut merged, span(lo, hi));
+        }
+        merged
+    }
+
+    #[test]
+    fn merge_overlapping_ranges() {
+        assert_eq!(merged(&[(1, 10), (5, 20)]), [span(1, 20)]);
+        assert_eq!(merged(&[(1, 10), (5, 20), (15, 30)]), [span(1, 30)]);
+    }
+
+    #[test]
+    fn merge_adjacent_ranges() {
+        assert_eq!(merged(&[(1, 10), (10, 20)]), [span(1, 20)]);
+    }
+
+    #[test]
+    fn merge_nested_ranges() {
+        assert_eq!(merged(&[(1, 30), (5, 10), (10, 30)]), [span(1, 30)]);
+        assert_eq!(merged(&[(1, 30), (1, 10)]), [span(1, 30)]);
+    }
+
+    #[test]
+    fn keep_disjoint_ranges() {
+        assert_eq!(
+            merged(&[(1, 10), (11, 20), (30, 40)]),
+            [span(1, 10), span(11, 20), span(30, 40)]
+        );
+    }
+
+    #[test]
+    fn look_up_positions() {
+        let ranges = merged(&[(10, 20), (30, 40)]);
+
+        // The start is in the range, the end is not
+        assert!(!is_in_removed(&ranges, BytePos(9)));
+        assert!(is_in_removed(&ranges, BytePos(10)));
+        assert!(is_in_removed(&ranges, BytePos(19)));
+        assert!(!is_in_removed(&ranges, BytePos(20)));
+        assert!(!is_in_removed(&ranges, BytePos(25)));
+        assert!(is_in_removed(&ranges, BytePos(30)));
+        assert!(!is_in_removed(&ranges, BytePos(40)));
+        assert!(!is_in_removed(&[], BytePos(10)));
+    }
+
+    #[test]
+    fn look_up_spans() {
+        let ranges = merged(&[(10, 20), (20, 25), (30, 40)]);
+
+        assert!(in_ranges(&ranges, span(10, 25)));
+        assert!(in_ranges(&ranges, span(12, 18)));
+        assert!(in_ranges(&ranges, span(30, 40)));
+        // Straddling the end or the gap between two ranges
+        assert!(!in_ranges(&ranges, span(20, 30)));
+        assert!(!in_ranges(&ranges, span(5, 15)));
+        assert!(!in_ranges(&ranges, span(35, 45)));
+        assert!(!in_ranges(&ranges, span(0, 5)));
+        assert!(!in_ranges(&[], span(10, 20)));
+    }
+}
@@ -1,13 +1,16 @@
-use serde_json::Value;
+use serde_json::{Map, Value};
 use swc_core::{
     atoms::Atom,
     common::DUMMY_SP,
     ecma::ast::{
-        ArrayLit, Bool, Expr, ExprOrSpread, KeyValueProp, Lit, Null, Number, ObjectLit, Prop,
-        PropName, PropOrSpread, Str,
+        ArrayLit, Bool, Expr, ExprOrSpread, Ident, KeyValueProp, Lit, Null, Number, ObjectLit,
+        Prop, PropName, PropOrSpread, Str,
     },
 };
 
+/// Metadata owned by the caller, e.g. the result of merging several config sources
+pub type OwnedMetadata = Value;
+
 /// This trait provides some utilities for `serde_json::Value` to handle external metadata
 pub trait Metadata {
     /// Query with JSONPath splitted by dot
@@ -15,7 +18,18 @@ pub trait Metadata {
     /// For example: `v.query("a.b.c")`
     fn query(&self, path: &str) -> Option<&Value>;
     /// Evaluate bool value with JSONPath splitted by dot
+    ///
+    /// Paths can be negated with `!`, combined with `&&` and `||` and grouped with parentheses.
+    /// `!` binds tighter than `&&`, which binds tighter than `||`, like in JavaScript.
+    ///
+    /// For example: `v.evaluate_bool("!a.b && (c.d || e)")`
     fn evaluate_bool(&self, path: &str) -> bool;
+    /// Like `evaluate_bool`, but values are evaluated with the truthiness of JavaScript
+    ///
+    /// `false`, `null`, `0`, `""` and missing paths are falsy, and any other value is truthy
+    fn evaluate_bool_js(&self, path: &str) -> bool;
+    /// Deep merge `override_` into a copy of the metadata, see `merge`
+    fn with_override(&self, override_: &Value) -> OwnedMetadata;
 }
 
 impl Metadata for Value {
@@ -28,67 +42,297 @@ impl Metadata for Value {
     }
 
     fn evaluate_bool(&self, path: &str) -> bool {
-        let Some(value) = self.query(path) else {
-            return false;
-        };
+        // Only values of bool type are evaluated, other types are falsy
+        evaluate_with(self, path, &|value| value.as_bool().unwrap_or(false))
+    }
+
+    fn evaluate_bool_js(&self, path: &str) -> bool {
+        evaluate_with(self, path, &|value| match value {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Number(number) => number.as_f64().is_some_and(|n| n != 0.0 && !n.is_nan()),
+            Value::String(s) => !s.is_empty(),
+            Value::Array(_) | Value::Object(_) => true,
+        })
+    }
+
+    fn with_override(&self, override_: &Value) -> OwnedMetadata {
+        merge(self, override_)
+    }
+}
 
-        // For simplification, we only evaluate values of bool type.
-        // We may evaluate other types like javascript
-        if let Some(bool) = value.as_bool() {
-            return bool;
+/// Deep merge two JSON values, like global defaults and per-project overrides.
+///
+/// Objects are merged key by key, so keys only present in `base` are kept.
+/// For any other value, including arrays, `override_` wins.
+pub fn merge(base: &Value, override_: &Value) -> Value {
+    match (base, override_) {
+        (Value::Object(base), Value::Object(override_)) => {
+            let mut merged = base.clone();
+            for (key, value) in override_ {
+                let value = match merged.get(key) {
+                    Some(base) => merge(base, value),
+                    None => value.clone(),
+                };
+                merged.insert(key.clone(), value);
+            }
+            Value::Object(merged)
         }
+        (_, override_) => override_.clone(),
+    }
+}
 
+/// Put the environment variables under the `env` key of a copy of the metadata, so conditions
+/// like `env.NODE_ENV` resolve.
+///
+/// Values already configured in the metadata win over the variables.
+pub fn inject_env(
+    meta_data: &Value,
+    vars: impl IntoIterator<Item = (String, Value)>,
+) -> OwnedMetadata {
+    let env = Value::Object(vars.into_iter().collect());
+    merge(&serde_json::json!({ "env": env }), meta_data)
+}
+
+/// The paths referenced by a condition like `!a.b && (c.d || e)`
+pub(crate) fn condition_paths(condition: &str) -> impl Iterator<Item = &str> {
+    let mut paths = Vec::new();
+    ConditionParser::new(condition, &mut |path| {
+        paths.push(path);
         false
+    })
+    .parse();
+    paths.into_iter()
+}
+
+/// Suggest the paths `path` may be a typo of, by swapping its first missing key for the nearest
+/// keys of the same object. The keys after it are kept.
+pub(crate) fn suggest_paths(v: &Value, path: &str) -> Vec<String> {
+    let segs: Vec<&str> = path.split('.').collect();
+    let mut parent = v;
+    for (i, seg) in segs.iter().enumerate() {
+        if let Some(child) = parent.get(seg) {
+            parent = child;
+            continue;
+        }
+        let Value::Object(siblings) = parent else {
+            return Vec::new();
+        };
+        let max_distance = (seg.chars().count() / 3).max(2);
+        let mut nearest: Vec<(usize, &String)> = siblings
+            .keys()
+            .map(|key| (edit_distance(seg, key), key))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .collect();
+        nearest.sort();
+        return nearest
+            .into_iter()
+            .take(3)
+            .map(|(_, key)| {
+                let mut suggestion = segs.clone();
+                suggestion[i] = key;
+                suggestion.join(".")
+            })
+            .collect();
+    }
+    Vec::new()
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(a != *b);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+/// Evaluate the `!`/`&&`/`||` expression of paths, where a present value is evaluated by `truthy`
+fn evaluate_with(v: &Value, condition: &str, truthy: &dyn Fn(&Value) -> bool) -> bool {
+    ConditionParser::new(condition, &mut |path| v.query(path).is_some_and(truthy)).parse()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+    Path(&'a str),
+}
+
+fn tokenize(condition: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = condition.trim_start();
+    while !rest.is_empty() {
+        let (token, len) = if rest.starts_with("&&") {
+            (Token::And, 2)
+        } else if rest.starts_with("||") {
+            (Token::Or, 2)
+        } else if rest.starts_with('!') {
+            (Token::Not, 1)
+        } else if rest.starts_with('(') {
+            (Token::Open, 1)
+        } else if rest.starts_with(')') {
+            (Token::Close, 1)
+        } else {
+            // A lone `&` or `|` is kept in the path, which then resolves to nothing
+            let len = rest
+                .find(|c: char| c.is_whitespace() || "!()".contains(c))
+                .unwrap_or(rest.len());
+            let len = rest[..len]
+                .find("&&")
+                .into_iter()
+                .chain(rest[..len].find("||"))
+                .min()
+                .unwrap_or(len);
+            (Token::Path(&rest[..len]), len)
+        };
+        tokens.push(token);
+        rest = rest[len..].trim_start();
+    }
+    tokens
+}
+
+/// A recursive descent parser of conditions, evaluating each path with `path`.
+///
+/// ```text
+/// or  := and ("||" and)*
+/// and := not ("&&" not)*
+/// not := "!" not | "(" or ")" | path
+/// ```
+///
+/// Every path is evaluated in document order, without short-circuiting. A malformed condition is
+/// read leniently: a missing operand is falsy, a missing `)` is assumed at the end, and what
+/// follows a complete expression, like a stray `)`, is ignored.
+struct ConditionParser<'a, 'f> {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token<'a>>>,
+    path: &'f mut dyn FnMut(&'a str) -> bool,
+}
+
+impl<'a, 'f> ConditionParser<'a, 'f> {
+    fn new(condition: &'a str, path: &'f mut dyn FnMut(&'a str) -> bool) -> Self {
+        ConditionParser {
+            tokens: tokenize(condition).into_iter().peekable(),
+            path,
+        }
+    }
+
+    fn parse(mut self) -> bool {
+        self.or()
+    }
+
+    fn or(&mut self) -> bool {
+        let mut result = self.and();
+        while self.tokens.next_if_eq(&Token::Or).is_some() {
+            result |= self.and();
+        }
+        result
+    }
+
+    fn and(&mut self) -> bool {
+        let mut result = self.not();
+        while self.tokens.next_if_eq(&Token::And).is_some() {
+            result &= self.not();
+        }
+        result
+    }
+
+    fn not(&mut self) -> bool {
+        match self.tokens.peek() {
+            Some(Token::Not) => {
+                self.tokens.next();
+                !self.not()
+            }
+            Some(Token::Open) => {
+                self.tokens.next();
+                let result = self.or();
+                self.tokens.next_if_eq(&Token::Close);
+                result
+            }
+            Some(Token::Path(path)) => {
+                let path = *path;
+                self.tokens.next();
+                (self.path)(path)
+            }
+            // A missing operand, which is left for the caller
+            _ => false,
+        }
     }
 }
 
 pub trait ToSwcAst {
+    /// Convert to a literal, where JSON `null` is the `null` literal
     fn to_ast(self) -> Expr;
+    /// Like `to_ast`, but JSON `null` is `undefined` at any depth
+    fn to_ast_with_undefined_for_null(self) -> Expr
+    where
+        Self: Sized,
+    {
+        self.to_ast()
+    }
 }
 
 impl ToSwcAst for Value {
     fn to_ast(self) -> Expr {
-        match self {
-            Value::Null => Expr::Lit(Lit::Null(Null { span: DUMMY_SP })),
-            Value::Bool(b) => Expr::Lit(Lit::Bool(Bool {
-                span: DUMMY_SP,
-                value: b,
-            })),
-            Value::Number(number) => Expr::Lit(Lit::Num(Number {
-                span: DUMMY_SP,
-                value: number.as_f64().unwrap(),
-                raw: None,
-            })),
-            Value::String(s) => Expr::Lit(Lit::Str(Str {
-                span: DUMMY_SP,
-                value: Atom::new(s),
-                raw: None,
-            })),
-            Value::Array(values) => Expr::Array(ArrayLit {
-                span: DUMMY_SP,
-                elems: values
-                    .into_iter()
-                    .map(|v| {
-                        Some(ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(v.to_ast()),
-                        })
-                    })
-                    .collect(),
-            }),
-            Value::Object(map) => Expr::Object(ObjectLit {
-                span: DUMMY_SP,
-                props: map
-                    .into_iter()
-                    .map(|(k, v)| {
-                        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                            key: PropName::Str(k.into()),
-                            value: Box::new(v.to_ast()),
-                        })))
+        value_to_ast(self, &|| Expr::Lit(Lit::Null(Null { span: DUMMY_SP })))
+    }
+
+    fn to_ast_with_undefined_for_null(self) -> Expr {
+        value_to_ast(self, &|| {
+            Expr::Ident(Ident::new_no_ctxt("undefined".into(), DUMMY_SP))
+        })
+    }
+}
+
+fn value_to_ast(value: Value, null: &dyn Fn() -> Expr) -> Expr {
+    match value {
+        Value::Null => null(),
+        Value::Bool(b) => Expr::Lit(Lit::Bool(Bool {
+            span: DUMMY_SP,
+            value: b,
+        })),
+        Value::Number(number) => Expr::Lit(Lit::Num(Number {
+            span: DUMMY_SP,
+            value: number.as_f64().unwrap(),
+            raw: None,
+        })),
+        Value::String(s) => Expr::Lit(Lit::Str(Str {
+            span: DUMMY_SP,
+            value: Atom::new(s),
+            raw: None,
+        })),
+        Value::Array(values) => Expr::Array(ArrayLit {
+            span: DUMMY_SP,
+            elems: values
+                .into_iter()
+                .map(|v| {
+                    Some(ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(value_to_ast(v, null)),
                     })
-                    .collect(),
-            }),
-        }
+                })
+                .collect(),
+        }),
+        Value::Object(map) => Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: map
+                .into_iter()
+                .map(|(k, v)| {
+                    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                        key: PropName::Str(k.into()),
+                        value: Box::new(value_to_ast(v, null)),
+                    })))
+                })
+                .collect(),
+        }),
     }
 }
 
@@ -101,3 +345,181 @@ impl ToSwcAst for String {
         }))
     }
 }
+
+/// The reverse of `ToSwcAst`, extracting a constant value back out of the ast, e.g. after inlining
+pub trait FromSwcAst: Sized {
+    /// `None` if the expression is not a literal, like an identifier or a call
+    fn from_ast(expr: &Expr) -> Option<Self>;
+}
+
+impl FromSwcAst for Value {
+    fn from_ast(expr: &Expr) -> Option<Self> {
+        match expr {
+            Expr::Lit(Lit::Null(_)) => Some(Value::Null),
+            Expr::Lit(Lit::Bool(b)) => Some(Value::Bool(b.value)),
+            Expr::Lit(Lit::Num(number)) => number_value(number.value),
+            Expr::Lit(Lit::Str(s)) => Some(Value::String(s.value.to_string())),
+            Expr::Array(array) => array
+                .elems
+                .iter()
+                .map(|elem| match elem {
+                    // A hole reads as `undefined`, which JSON has no room for
+                    None => Some(Value::Null),
+                    Some(ExprOrSpread { spread: None, expr }) => Value::from_ast(expr),
+                    Some(ExprOrSpread {
+                        spread: Some(_), ..
+                    }) => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(Value::Array),
+            Expr::Object(object) => object
+                .props
+                .iter()
+                .map(|prop| {
+                    let PropOrSpread::Prop(prop) = prop else {
+                        return None;
+                    };
+                    let Prop::KeyValue(KeyValueProp { key, value }) = &**prop else {
+                        return None;
+                    };
+                    let key = match key {
+                        PropName::Ident(ident) => ident.sym.to_string(),
+                        PropName::Str(s) => s.value.to_string(),
+                        PropName::Num(number) => number.value.to_string(),
+                        PropName::Computed(_) | PropName::BigInt(_) => return None,
+                    };
+                    Some((key, Value::from_ast(value)?))
+                })
+                .collect::<Option<Map<_, _>>>()
+                .map(Value::Object),
+            _ => None,
+        }
+    }
+}
+
+/// Integral numbers are kept as integers, so that `1` round-trips as `1` rather than `1.0`
+fn number_value(value: f64) -> Option<Value> {
+    const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+    if value.fract() == 0.0 && value.abs() <= MAX_SAFE_INTEGER {
+        return Some(Value::from(value as i64));
+    }
+    serde_json::Number::from_f64(value).map(Value::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let v = json!({ "t": true, "f": false });
+
+        assert!(v.evaluate_bool("t || f && f"));
+        assert!(v.evaluate_bool("f && f || t"));
+        assert!(!v.evaluate_bool("f || t && f"));
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and() {
+        let v = json!({ "t": true, "f": false });
+
+        assert!(v.evaluate_bool("!f && t"));
+        assert!(!v.evaluate_bool("!t && t"));
+        assert!(v.evaluate_bool("!!t"));
+    }
+
+    #[test]
+    fn parentheses_group() {
+        let v = json!({ "t": true, "f": false });
+
+        assert!(!v.evaluate_bool("(t || f) && f"));
+        assert!(v.evaluate_bool("t || (f && f)"));
+        assert!(!v.evaluate_bool("!(f || t)"));
+        assert!(v.evaluate_bool("!(f && t) && (t)"));
+        assert!(v.evaluate_bool("((t))"));
+    }
+
+    #[test]
+    fn spaces_are_optional() {
+        let v = json!({ "a": { "b": true }, "c": false });
+
+        assert!(v.evaluate_bool("!c&&(a.b||c)"));
+        assert!(v.evaluate_bool("  a.b  "));
+    }
+
+    #[test]
+    fn malformed_conditions_are_lenient() {
+        let v = json!({ "t": true, "f": false });
+
+        // A missing `)` is assumed at the end
+        assert!(v.evaluate_bool("(f || t"));
+        // A missing operand is falsy
+        assert!(!v.evaluate_bool("t &&"));
+        assert!(!v.evaluate_bool(""));
+        // A lone `&` is part of the path
+        assert!(!v.evaluate_bool("t&t"));
+        // What follows a stray `)` is ignored
+        assert!(v.evaluate_bool("t) && f"));
+    }
+
+    #[test]
+    fn js_truthiness_follows_the_same_grammar() {
+        let v = json!({ "zero": 0, "name": "x", "empty": "" });
+
+        assert!(v.evaluate_bool_js("(zero || name) && !empty"));
+        assert!(!v.evaluate_bool_js("zero || (name && empty)"));
+    }
+
+    #[test]
+    fn values_round_trip_through_the_ast() {
+        let values = [
+            json!(null),
+            json!(true),
+            json!(0),
+            json!(-42),
+            json!(1.5),
+            json!("text"),
+            json!([]),
+            json!([1, "two", null, [false]]),
+            json!({}),
+            json!({ "a": { "b": [1, { "c": null }], "d": "e" }, "f": 2.5 }),
+        ];
+
+        for value in values {
+            assert_eq!(Value::from_ast(&value.clone().to_ast()), Some(value));
+        }
+    }
+
+    #[test]
+    fn undefined_is_not_a_json_value() {
+        let undefined = json!(null).to_ast_with_undefined_for_null();
+        let nested = json!({ "a": [null] }).to_ast_with_undefined_for_null();
+
+        assert_eq!(Value::from_ast(&undefined), None);
+        assert_eq!(Value::from_ast(&nested), None);
+    }
+
+    #[test]
+    fn non_literals_are_not_values() {
+        let ident = Expr::Ident(Ident::new_no_ctxt("FLAG".into(), DUMMY_SP));
+        let array = Expr::Array(ArrayLit {
+            span: DUMMY_SP,
+            elems: vec![Some(ExprOrSpread {
+                spread: None,
+                expr: Box::new(ident.clone()),
+            })],
+        });
+
+        assert_eq!(Value::from_ast(&ident), None);
+        assert_eq!(Value::from_ast(&array), None);
+    }
+
+    #[test]
+    fn condition_paths_skip_the_operators() {
+        let paths: Vec<_> = condition_paths("!a.b && (c || !(d.e))").collect();
+
+        assert_eq!(paths, ["a.b", "c", "d.e"]);
+    }
+}
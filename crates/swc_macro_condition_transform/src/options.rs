@@ -0,0 +1,49 @@
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{Expr, Lit, Null, Number, UnaryExpr, UnaryOp},
+};
+
+/// Options of `condition_transform`
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// What an expression in a removed region is replaced with
+    pub removed_expr: RemovedExprReplacement,
+    /// Only `true` satisfies a condition, as in `Metadata::evaluate_bool`. Otherwise values are
+    /// evaluated with the truthiness of JavaScript, so `"2.0"` or `1` satisfies it as well.
+    pub strict_bool: bool,
+    /// Fail with `ConditionTransformError::UnknownPaths` when a condition, or a define-inline
+    /// without a default, references a path missing from the metadata, e.g. a typo'd flag.
+    /// Otherwise a missing path is falsy.
+    pub strict: bool,
+}
+
+/// The replacement of an expression in a removed region, where the expression can't be dropped
+/// like in `const a = /* @common:if [...] */ value /* @common:endif */;`
+#[derive(Debug, Clone, Default)]
+pub enum RemovedExprReplacement {
+    /// `null`
+    #[default]
+    Null,
+    /// `void 0`, which triggers default parameters and default values of destructuring
+    Undefined,
+    /// An expression provided by the caller
+    Expr(Box<Expr>),
+}
+
+impl RemovedExprReplacement {
+    pub(crate) fn to_expr(&self) -> Expr {
+        match self {
+            RemovedExprReplacement::Null => Expr::Lit(Lit::Null(Null { span: DUMMY_SP })),
+            RemovedExprReplacement::Undefined => Expr::Unary(UnaryExpr {
+                span: DUMMY_SP,
+                op: UnaryOp::Void,
+                arg: Box::new(Expr::Lit(Lit::Num(Number {
+                    span: DUMMY_SP,
+                    value: 0.0,
+                    raw: None,
+                }))),
+            }),
+            RemovedExprReplacement::Expr(expr) => (**expr).clone(),
+        }
+    }
+}
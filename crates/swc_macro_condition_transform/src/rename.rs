@@ -0,0 +1,315 @@
+use swc_core::{
+    atoms::Atom,
+    common::Span,
+    ecma::{
+        ast::{
+            ArrowExpr, AssignPat, AssignPatProp, BlockStmt, BlockStmtOrExpr, BreakStmt,
+            CatchClause, Class, ClassExpr, Constructor, ContinueStmt, Decl, ExportNamedSpecifier,
+            FnExpr, ForHead, ForInStmt, ForOfStmt, ForStmt, Function, GetterProp, Ident, IdentName,
+            ImportNamedSpecifier, KeyValuePatProp, KeyValueProp, LabeledStmt, ModuleExportName,
+            NamedExport, ObjectPatProp, Pat, Prop, PropName, SetterProp, Stmt, SwitchStmt, VarDecl,
+            VarDeclKind, VarDeclOrExpr,
+        },
+        visit::{Visit, VisitMut, VisitMutWith, VisitWith},
+    },
+};
+
+use crate::{LineCol, bindings::DeclaredNames};
+
+/// A top-level binding renamed by `rename`
+pub struct Rename {
+    pub from: Atom,
+    pub to: Atom,
+    pub span: Span,
+    pub loc: Option<LineCol>,
+}
+
+/// Rename a top-level binding and its references, leaving alone the scopes that shadow it.
+///
+/// Like `DeclaredNames`, the bindings are told apart by their names since the resolver hasn't
+/// run yet. The result must be discarded when either flag is set:
+/// - `captured` when a renamed reference lies in a scope declaring the new name
+/// - `referenced` when the new name is referenced outside the scopes declaring it, e.g. a call to
+///   a global of that name, which the renamed binding would capture
+pub struct ScopedRenamer {
+    from: Atom,
+    to: Atom,
+    /// The number of enclosing scopes declaring `from`
+    shadowing: usize,
+    /// The number of enclosing scopes declaring `to`
+    capturing: usize,
+    pub captured: bool,
+    pub referenced: bool,
+}
+
+impl ScopedRenamer {
+    pub fn new(from: Atom, to: Atom) -> Self {
+        ScopedRenamer {
+            from,
+            to,
+            shadowing: 0,
+            capturing: 0,
+            captured: false,
+            referenced: false,
+        }
+    }
+
+    /// Visit a nested scope declaring `names`
+    fn scoped(&mut self, names: DeclaredNames, visit: impl FnOnce(&mut Self)) {
+        let shadows = names.0.contains(&self.from) as usize;
+        let captures = names.0.contains(&self.to) as usize;
+        self.shadowing += shadows;
+        self.capturing += captures;
+        visit(self);
+        self.shadowing -= shadows;
+        self.capturing -= captures;
+    }
+
+    /// Whether `sym` refers to the renamed binding
+    fn renames(&self, sym: &Atom) -> bool {
+        *sym == self.from && self.shadowing == 0
+    }
+}
+
+fn declared_names<N: VisitWith<DeclaredNames> + ?Sized>(node: &N) -> DeclaredNames {
+    let mut names = DeclaredNames::default();
+    node.visit_with(&mut names);
+    names
+}
+
+/// The names declared in a function body, including the `var`s of nested blocks
+fn function_scope_names(body: &BlockStmt) -> DeclaredNames {
+    let mut names = declared_names(&body.stmts);
+    body.visit_with(&mut HoistedVars(&mut names));
+    names
+}
+
+/// Collect the names of `var` declarations, which are hoisted out of blocks
+struct HoistedVars<'a>(&'a mut DeclaredNames);
+
+impl Visit for HoistedVars<'_> {
+    fn visit_var_decl(&mut self, node: &VarDecl) {
+        if node.kind == VarDeclKind::Var {
+            node.decls.visit_with(self.0);
+        }
+    }
+
+    fn visit_function(&mut self, _: &Function) {}
+
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+    fn visit_class(&mut self, _: &Class) {}
+}
+
+impl VisitMut for ScopedRenamer {
+    fn visit_mut_ident(&mut self, node: &mut Ident) {
+        if self.renames(&node.sym) {
+            self.captured |= self.capturing > 0;
+            node.sym = self.to.clone();
+        } else if node.sym == self.to {
+            self.referenced |= self.capturing == 0;
+        }
+    }
+
+    fn visit_mut_function(&mut self, node: &mut Function) {
+        let mut names = declared_names(&node.params);
+        if let Some(body) = &node.body {
+            names.0.extend(function_scope_names(body).0);
+        }
+        self.scoped(names, |this| node.visit_mut_children_with(this));
+    }
+
+    fn visit_mut_constructor(&mut self, node: &mut Constructor) {
+        let mut names = declared_names(&node.params);
+        if let Some(body) = &node.body {
+            names.0.extend(function_scope_names(body).0);
+        }
+        self.scoped(names, |this| node.visit_mut_children_with(this));
+    }
+
+    // Getters and setters are not `Function`s
+    fn visit_mut_getter_prop(&mut self, node: &mut GetterProp) {
+        node.key.visit_mut_with(self);
+        let names = node
+            .body
+            .as_ref()
+            .map(function_scope_names)
+            .unwrap_or_default();
+        self.scoped(names, |this| node.body.visit_mut_with(this));
+    }
+
+    fn visit_mut_setter_prop(&mut self, node: &mut SetterProp) {
+        node.key.visit_mut_with(self);
+        let mut names = declared_names(&node.param);
+        if let Some(body) = &node.body {
+            names.0.extend(function_scope_names(body).0);
+        }
+        self.scoped(names, |this| {
+            node.param.visit_mut_with(this);
+            node.body.visit_mut_with(this);
+        });
+    }
+
+    fn visit_mut_arrow_expr(&mut self, node: &mut ArrowExpr) {
+        let mut names = declared_names(&node.params);
+        if let BlockStmtOrExpr::BlockStmt(body) = &*node.body {
+            names.0.extend(function_scope_names(body).0);
+        }
+        self.scoped(names, |this| node.visit_mut_children_with(this));
+    }
+
+    // The name of a function or class expression is only bound inside it
+    fn visit_mut_fn_expr(&mut self, node: &mut FnExpr) {
+        let names = DeclaredNames(node.ident.iter().map(|ident| ident.sym.clone()).collect());
+        self.scoped(names, |this| node.function.visit_mut_with(this));
+    }
+
+    fn visit_mut_class_expr(&mut self, node: &mut ClassExpr) {
+        let names = DeclaredNames(node.ident.iter().map(|ident| ident.sym.clone()).collect());
+        self.scoped(names, |this| node.class.visit_mut_with(this));
+    }
+
+    fn visit_mut_block_stmt(&mut self, node: &mut BlockStmt) {
+        self.scoped(lexical_names(&node.stmts), |this| {
+            node.visit_mut_children_with(this)
+        });
+    }
+
+    fn visit_mut_switch_stmt(&mut self, node: &mut SwitchStmt) {
+        node.discriminant.visit_mut_with(self);
+        let names = DeclaredNames(
+            node.cases
+                .iter()
+                .flat_map(|case| lexical_names(&case.cons).0)
+                .collect(),
+        );
+        self.scoped(names, |this| node.cases.visit_mut_with(this));
+    }
+
+    fn visit_mut_catch_clause(&mut self, node: &mut CatchClause) {
+        self.scoped(declared_names(&node.param), |this| {
+            node.visit_mut_children_with(this)
+        });
+    }
+
+    fn visit_mut_for_stmt(&mut self, node: &mut ForStmt) {
+        let names = match &node.init {
+            Some(VarDeclOrExpr::VarDecl(var_decl)) => block_scoped_names(var_decl),
+            _ => DeclaredNames::default(),
+        };
+        self.scoped(names, |this| node.visit_mut_children_with(this));
+    }
+
+    fn visit_mut_for_in_stmt(&mut self, node: &mut ForInStmt) {
+        let names = for_head_names(&node.left);
+        self.scoped(names, |this| node.visit_mut_children_with(this));
+    }
+
+    fn visit_mut_for_of_stmt(&mut self, node: &mut ForOfStmt) {
+        let names = for_head_names(&node.left);
+        self.scoped(names, |this| node.visit_mut_children_with(this));
+    }
+
+    // Keep the property names of shorthands
+    fn visit_mut_prop(&mut self, node: &mut Prop) {
+        if let Prop::Shorthand(ident) = node
+            && self.renames(&ident.sym)
+        {
+            *node = Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(IdentName::new(self.from.clone(), ident.span)),
+                value: ident.clone().into(),
+            });
+        }
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_object_pat_prop(&mut self, node: &mut ObjectPatProp) {
+        if let ObjectPatProp::Assign(AssignPatProp { key, value, span }) = node
+            && self.renames(&key.sym)
+        {
+            let left = Box::new(Pat::Ident(key.clone()));
+            *node = ObjectPatProp::KeyValue(KeyValuePatProp {
+                key: PropName::Ident(IdentName::new(self.from.clone(), key.span)),
+                value: match value.take() {
+                    Some(right) => Box::new(Pat::Assign(AssignPat {
+                        span: *span,
+                        left,
+                        right,
+                    })),
+                    None => left,
+                },
+            });
+        }
+        node.visit_mut_children_with(self);
+    }
+
+    // Keep the imported name
+    fn visit_mut_import_named_specifier(&mut self, node: &mut ImportNamedSpecifier) {
+        if node.local.sym == self.from {
+            node.imported
+                .get_or_insert_with(|| ModuleExportName::Ident(node.local.clone()));
+            node.local.sym = self.to.clone();
+        }
+    }
+
+    // A re-export doesn't reference the bindings of the module
+    fn visit_mut_named_export(&mut self, node: &mut NamedExport) {
+        if node.src.is_none() {
+            node.specifiers.visit_mut_with(self);
+        }
+    }
+
+    // The exported name follows the binding, unless it is aliased to another name
+    fn visit_mut_export_named_specifier(&mut self, node: &mut ExportNamedSpecifier) {
+        let ModuleExportName::Ident(orig) = &mut node.orig else {
+            return;
+        };
+        if orig.sym != self.from {
+            return;
+        }
+        orig.sym = self.to.clone();
+        if let Some(ModuleExportName::Ident(exported)) = &mut node.exported
+            && exported.sym == self.from
+        {
+            exported.sym = self.to.clone();
+        }
+    }
+
+    // Labels are not bindings
+    fn visit_mut_labeled_stmt(&mut self, node: &mut LabeledStmt) {
+        node.body.visit_mut_with(self);
+    }
+
+    fn visit_mut_break_stmt(&mut self, _: &mut BreakStmt) {}
+
+    fn visit_mut_continue_stmt(&mut self, _: &mut ContinueStmt) {}
+}
+
+/// The names declared by a block's statements that are scoped to it: `let`, `const`, classes and
+/// functions. A `var` belongs to the enclosing function, or to the top level.
+fn lexical_names(stmts: &[Stmt]) -> DeclaredNames {
+    let mut names = DeclaredNames::default();
+    for stmt in stmts {
+        match stmt {
+            Stmt::Decl(Decl::Var(var_decl)) => names.0.extend(block_scoped_names(var_decl).0),
+            Stmt::Decl(decl) => decl.visit_with(&mut names),
+            _ => {}
+        }
+    }
+    names
+}
+
+/// The names of a `let` or `const` declaration, a `var` belongs to the enclosing function
+fn block_scoped_names(var_decl: &VarDecl) -> DeclaredNames {
+    match var_decl.kind {
+        VarDeclKind::Var => DeclaredNames::default(),
+        _ => declared_names(&var_decl.decls),
+    }
+}
+
+fn for_head_names(head: &ForHead) -> DeclaredNames {
+    match head {
+        ForHead::VarDecl(var_decl) => block_scoped_names(var_decl),
+        _ => DeclaredNames::default(),
+    }
+}
@@ -0,0 +1,167 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::Serialize;
+use serde_json::Value;
+use swc_core::common::Span;
+
+use crate::{Diagnostic, LineCol, UnknownPath};
+
+/// What a transform did, for callers that need more than the output code like CI checks
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TransformReport {
+    /// The evaluated `if`/`unless` regions in document order.
+    /// A region nested in a removed one is not evaluated.
+    pub regions: Vec<RegionReport>,
+    /// The resolved `define-inline` directives in document order
+    pub inlines: Vec<InlineReport>,
+    /// The replacements and insertions that are not applied in document order
+    pub skipped: Vec<SkippedReport>,
+    /// The paths missing from the metadata in document order, which fail the transform in
+    /// strict mode
+    pub unknown_paths: Vec<UnknownPathReport>,
+    /// The recoverable problems in the order they are found, e.g. an unknown directive or one
+    /// conflicting with another
+    pub diagnostics: Vec<DiagnosticReport>,
+    pub removed: RemovedCounts,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RegionReport {
+    pub directive: String,
+    pub condition: String,
+    /// The evaluation result of the condition, which keeps an `unless` region when false
+    pub result: bool,
+    pub kept: bool,
+    pub span: ReportSpan,
+    pub loc: Option<LineCol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InlineReport {
+    /// The metadata path of the value
+    pub path: String,
+    pub value: Value,
+    /// Whether the value comes from the `default` attr as the path is missing
+    pub default_used: bool,
+    pub span: ReportSpan,
+    pub loc: Option<LineCol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkippedReport {
+    pub directive: String,
+    pub reason: String,
+    pub span: ReportSpan,
+    pub loc: Option<LineCol>,
+}
+
+impl SkippedReport {
+    /// Removal wins over the replacements and insertions in a removed region
+    pub(crate) fn inside_removed(directive: &str, span: Span, loc: Option<LineCol>) -> Self {
+        SkippedReport {
+            directive: directive.to_string(),
+            reason: "inside removed region".to_string(),
+            span: span.into(),
+            loc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnknownPathReport {
+    pub path: String,
+    pub directive: String,
+    pub suggestions: Vec<String>,
+    pub span: ReportSpan,
+    pub loc: Option<LineCol>,
+}
+
+impl From<&UnknownPath> for UnknownPathReport {
+    fn from(path: &UnknownPath) -> Self {
+        UnknownPathReport {
+            path: path.path.clone(),
+            directive: path.directive.clone(),
+            suggestions: path.suggestions.clone(),
+            span: path.span.into(),
+            loc: path.loc,
+        }
+    }
+}
+
+/// A diagnostic in a serializable form, for callers that can't take the `Diagnostic` itself
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiagnosticReport {
+    pub message: String,
+    pub span: ReportSpan,
+    pub loc: Option<LineCol>,
+}
+
+impl From<&Diagnostic> for DiagnosticReport {
+    fn from(diagnostic: &Diagnostic) -> Self {
+        DiagnosticReport {
+            message: diagnostic.message.clone(),
+            span: diagnostic.span.into(),
+            loc: diagnostic.loc,
+        }
+    }
+}
+
+/// The nodes removed by the pass, not counting the nodes inside them
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RemovedCounts {
+    pub module_items: usize,
+    pub stmts: usize,
+    pub exprs: usize,
+    /// The declarators dropped from a declaration, which is counted too once none is left
+    pub declarators: usize,
+    pub props: usize,
+    pub class_members: usize,
+    pub switch_cases: usize,
+    /// The array elements dropped, a hole written in the source is never counted
+    pub array_elements: usize,
+    /// The call arguments dropped, not counting the ones replaced with `mode="null"`
+    pub call_args: usize,
+    /// The import and export specifiers dropped from a declaration, which is counted too once none
+    /// is left
+    pub specifiers: usize,
+}
+
+/// Byte range in the original source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ReportSpan {
+    pub lo: u32,
+    pub hi: u32,
+}
+
+impl From<Span> for ReportSpan {
+    fn from(span: Span) -> Self {
+        ReportSpan {
+            lo: span.lo.0,
+            hi: span.hi.0,
+        }
+    }
+}
+
+/// The report of a transform, shared with its pass as the removal counts are only known once
+/// it has been applied.
+#[derive(Debug, Clone, Default)]
+pub struct TransformReportHandle(Rc<RefCell<TransformReport>>);
+
+impl TransformReportHandle {
+    pub(crate) fn new(report: TransformReport) -> Self {
+        TransformReportHandle(Rc::new(RefCell::new(report)))
+    }
+
+    pub(crate) fn count_removed(&self, count: impl FnOnce(&mut RemovedCounts)) {
+        count(&mut self.0.borrow_mut().removed);
+    }
+
+    pub(crate) fn push_diagnostic(&self, diagnostic: &Diagnostic) {
+        self.0.borrow_mut().diagnostics.push(diagnostic.into());
+    }
+
+    /// Take the report
+    pub fn take(&self) -> TransformReport {
+        self.0.take()
+    }
+}
@@ -0,0 +1,40 @@
+use swc_core::{
+    common::{DUMMY_SP, FileName, SourceMap, Span, sync::Lrc},
+    ecma::{
+        ast::Stmt,
+        parser::{EsSyntax, Parser, StringInput, Syntax},
+        visit::{VisitMut, VisitMutWith},
+    },
+};
+
+/// Parse a snippet of javascript code provided by metadata into statements.
+///
+/// The snippet is parsed in its own `SourceMap`, so all the spans are dropped
+/// to avoid colliding with the positions of the file being transformed.
+pub fn parse_snippet(code: &str) -> Result<Vec<Stmt>, String> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(FileName::Anon.into(), code.to_owned());
+    let mut parser = Parser::new(
+        Syntax::Es(EsSyntax::default()),
+        StringInput::from(&*fm),
+        None,
+    );
+    let mut script = parser
+        .parse_script()
+        .map_err(|err| err.kind().msg().into_owned())?;
+    // Errors recovered by the parser still make the snippet invalid
+    if let Some(err) = parser.take_errors().into_iter().next() {
+        return Err(err.kind().msg().into_owned());
+    }
+
+    script.visit_mut_with(&mut DropSpan);
+    Ok(script.body)
+}
+
+struct DropSpan;
+
+impl VisitMut for DropSpan {
+    fn visit_mut_span(&mut self, span: &mut Span) {
+        *span = DUMMY_SP;
+    }
+}
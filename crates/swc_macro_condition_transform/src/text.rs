@@ -0,0 +1,41 @@
+use swc_core::{
+    atoms::Atom,
+    ecma::{
+        ast::{Str, TplElement},
+        visit::VisitMut,
+    },
+};
+
+/// Substitute a placeholder inside the string literals and template literal text of a node
+pub struct TextReplacer {
+    pub placeholder: String,
+    pub text: String,
+}
+
+impl VisitMut for TextReplacer {
+    fn visit_mut_str(&mut self, node: &mut Str) {
+        if !node.value.contains(&*self.placeholder) {
+            return;
+        }
+        node.value = Atom::from(node.value.replace(&*self.placeholder, &self.text));
+        // Let the codegen escape the new value
+        node.raw = None;
+    }
+
+    fn visit_mut_tpl_element(&mut self, node: &mut TplElement) {
+        if !node.raw.contains(&*self.placeholder) {
+            return;
+        }
+        // Unlike string literals, the codegen prints the raw text of templates as is
+        let escaped = self
+            .text
+            .replace('\\', "\\\\")
+            .replace('`', "\\`")
+            .replace("${", "\\${");
+        node.raw = Atom::from(node.raw.replace(&*self.placeholder, &escaped));
+        node.cooked = node
+            .cooked
+            .as_ref()
+            .map(|cooked| Atom::from(cooked.replace(&*self.placeholder, &self.text)));
+    }
+}
@@ -8,6 +8,7 @@ use swc_macro_parser::MacroParser;
 
 pub fn main() {
     let path = std::env::args().nth(1).unwrap_or("test.js".to_owned());
+    let namespace = std::env::args().nth(2).unwrap_or("common".to_owned());
     let source = fs::read_to_string(path).unwrap();
 
     let (_program, comments) = {
@@ -24,7 +25,7 @@ pub fn main() {
         (program, comments)
     };
 
-    let parser = MacroParser::new("common");
+    let parser = MacroParser::new(namespace);
     let macros = parser.parse(&comments);
     println!("{:?}", macros);
 }
@@ -1,6 +1,6 @@
 use std::sync::LazyLock;
 
-use regex::Regex;
+use regex::{Captures, Regex};
 use rustc_hash::FxHashMap;
 use swc_core::common::{
     BytePos, Span,
@@ -21,12 +21,14 @@ static ATTR_REGEX: LazyLock<Regex> = LazyLock::new(|| {
 /// `MacroParser` is a regex-based parser that parses the macros in the comments.
 /// It only focus on the macros with specified namespace for performance.
 pub struct MacroParser {
-    namespace: &'static str,
+    namespace: String,
 }
 
 impl MacroParser {
-    pub fn new(namespace: &'static str) -> Self {
-        MacroParser { namespace }
+    pub fn new(namespace: impl Into<String>) -> Self {
+        MacroParser {
+            namespace: namespace.into(),
+        }
     }
 
     pub fn parse(&self, swc_comments: &SingleThreadedComments) -> Vec<(BytePos, MacroNode)> {
@@ -35,21 +37,61 @@ impl MacroParser {
         let mut macros = Vec::new();
         for (ast_pos, comments) in leading.iter_mut().chain(trailing.iter_mut()) {
             comments.retain(|comment| {
-                if let Some(macro_node) = self.parse_macro(comment) {
-                    macros.push((*ast_pos, macro_node));
-                    return false;
-                }
-                true
+                let (macro_nodes, all_recognized) = self.parse_macros(comment);
+                // A comment mixing in macros of other namespaces is kept for them
+                let keep = macro_nodes.is_empty() || !all_recognized;
+                macros.extend(
+                    macro_nodes
+                        .into_iter()
+                        .map(|macro_node| (*ast_pos, macro_node)),
+                );
+                keep
             });
         }
 
         macros
     }
 
-    fn parse_macro(&self, comment: &Comment) -> Option<MacroNode> {
-        let caps = MACRO_REGEX.captures_iter(&comment.text).next()?;
+    /// Like `parse`, but leaves the comments intact, e.g. to list the macros for editor tooling.
+    /// The macros are sorted by their position in the source.
+    pub fn scan(&self, swc_comments: &SingleThreadedComments) -> Vec<(BytePos, MacroNode)> {
+        let (leading, trailing) = swc_comments.borrow_all();
+
+        let mut macros: Vec<_> = leading
+            .iter()
+            .chain(trailing.iter())
+            .flat_map(|(ast_pos, comments)| {
+                comments.iter().flat_map(|comment| {
+                    let (macro_nodes, _) = self.parse_macros(comment);
+                    macro_nodes
+                        .into_iter()
+                        .map(|macro_node| (*ast_pos, macro_node))
+                })
+            })
+            .collect();
+        macros.sort_by_key(|(_, macro_node)| macro_node.span.lo);
+
+        macros
+    }
+
+    /// Parse all the macros of the namespace in a comment, in order, along with whether the
+    /// comment has no macro of another namespace
+    fn parse_macros(&self, comment: &Comment) -> (Vec<MacroNode>, bool) {
+        let mut all_recognized = true;
+        let macro_nodes = MACRO_REGEX
+            .captures_iter(&comment.text)
+            .filter_map(|caps| {
+                let macro_node = self.parse_macro(comment, &caps);
+                all_recognized &= macro_node.is_some();
+                macro_node
+            })
+            .collect();
+        (macro_nodes, all_recognized)
+    }
+
+    fn parse_macro(&self, comment: &Comment, caps: &Captures) -> Option<MacroNode> {
         let namespace = caps.name("namespace")?;
-        if namespace.as_str() != self.namespace {
+        if namespace.as_str() != self.namespace.as_str() {
             return None;
         }
 
@@ -93,3 +135,68 @@ pub struct MacroNode {
     pub directive: String,
     pub attrs: FxHashMap<String, String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::comments::{CommentKind, Comments};
+
+    use super::*;
+
+    fn comments(texts: &[&str]) -> SingleThreadedComments {
+        let comments = SingleThreadedComments::default();
+        let mut pos = 1;
+        for text in texts {
+            let hi = pos + text.len() as u32 + 4;
+            comments.add_leading(
+                BytePos(hi + 1),
+                Comment {
+                    kind: CommentKind::Block,
+                    span: Span::new(BytePos(pos), BytePos(hi)),
+                    text: (*text).into(),
+                },
+            );
+            pos = hi + 2;
+        }
+        comments
+    }
+
+    #[test]
+    fn parse_with_a_namespace_built_at_runtime() {
+        let namespace = ["my", "app"].join("-");
+        let parser = MacroParser::new(namespace);
+        let comments = comments(&[
+            r#" @my-app:if [condition="features.a"] "#,
+            r#" @common:if [condition="features.b"] "#,
+        ]);
+
+        let macros = parser.parse(&comments);
+
+        assert_eq!(macros.len(), 1);
+        assert_eq!(macros[0].1.namespace, "my-app");
+        assert_eq!(macros[0].1.directive, "if");
+        assert_eq!(macros[0].1.attrs["condition"], "features.a");
+    }
+
+    #[test]
+    fn scan_leaves_the_comments_unchanged() {
+        let parser = MacroParser::new("common");
+        let comments = comments(&[
+            r#" @common:if [condition="features.a"] "#,
+            " a plain comment ",
+            r#" @common:define-inline [value="build.a"] @other:if "#,
+        ]);
+        let snapshot = || {
+            let (leading, trailing) = comments.borrow_all();
+            ((*leading).clone(), (*trailing).clone())
+        };
+        let before = snapshot();
+
+        let scanned = parser.scan(&comments);
+
+        assert_eq!(scanned.len(), 2);
+        assert_eq!(snapshot(), before);
+        // A later parse still finds the macros, and only then removes their comments
+        assert_eq!(parser.parse(&comments).len(), 2);
+        assert_ne!(snapshot(), before);
+    }
+}
@@ -26,6 +26,13 @@ pub fn main() {
         }
     });
 
-    let ret = swc_macro_wasm::optimize::optimize(source, config);
-    println!("{}", ret);
+    match swc_macro_wasm::optimize::optimize(source, config) {
+        Ok(output) => {
+            for diagnostic in output.diagnostics {
+                eprintln!("warning: {}", diagnostic);
+            }
+            println!("{}", output.code);
+        }
+        Err(err) => eprintln!("error: {}", err),
+    }
 }
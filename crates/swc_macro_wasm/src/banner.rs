@@ -0,0 +1,52 @@
+use rustc_hash::FxHashSet;
+use swc_common::{
+    BytePos, Span, Spanned,
+    comments::{Comment, CommentKind, SingleThreadedComments},
+};
+use swc_ecma_ast::Program;
+use swc_ecma_visit::{Visit, VisitWith};
+
+/// Move the legal banners attached to removed nodes to the start of the program.
+///
+/// The emitter only prints the comments at the positions of the nodes it emits, so a
+/// `/*! ... */` banner on a statement removed by the transform or DCE would be lost.
+pub fn relocate_banners(program: &Program, comments: &SingleThreadedComments) {
+    let mut anchors = AnchorCollector::default();
+    program.visit_with(&mut anchors);
+
+    let (mut leading, mut trailing) = comments.borrow_all_mut();
+    let mut orphans = Vec::new();
+    for (pos, comments) in leading.iter_mut().chain(trailing.iter_mut()) {
+        if anchors.0.contains(pos) {
+            continue;
+        }
+        orphans.extend(comments.extract_if(.., |comment| is_banner(comment)));
+    }
+    if orphans.is_empty() {
+        return;
+    }
+
+    orphans.sort_by_key(|comment| comment.span.lo);
+    let anchored = leading.entry(program.span().lo).or_default();
+    orphans.append(anchored);
+    *anchored = orphans;
+}
+
+/// `/*! ... */`, or a block comment with `@license` or `@preserve`
+fn is_banner(comment: &Comment) -> bool {
+    comment.kind == CommentKind::Block
+        && (comment.text.starts_with('!')
+            || comment.text.contains("@license")
+            || comment.text.contains("@preserve"))
+}
+
+/// Collect the positions the emitter looks up comments at
+#[derive(Default)]
+struct AnchorCollector(FxHashSet<BytePos>);
+
+impl Visit for AnchorCollector {
+    fn visit_span(&mut self, span: &Span) {
+        self.0.insert(span.lo);
+        self.0.insert(span.hi);
+    }
+}
@@ -176,10 +176,14 @@ impl Data {
                 }
             }
             None => {
-                self.graph.add_edge(from, to, VarInfo {
-                    usage: u32::from(!assign),
-                    assign: u32::from(assign),
-                });
+                self.graph.add_edge(
+                    from,
+                    to,
+                    VarInfo {
+                        usage: u32::from(!assign),
+                        assign: u32::from(assign),
+                    },
+                );
             }
         };
     }
@@ -893,12 +897,15 @@ impl VisitMut for TreeShaker {
                     Expr::Fn(FnExpr {
                         ident: None,
                         function: f,
-                    }) if matches!(&**f, Function {
-                        is_async: false,
-                        is_generator: false,
-                        body: Some(..),
-                        ..
-                    }) =>
+                    }) if matches!(
+                        &**f,
+                        Function {
+                            is_async: false,
+                            is_generator: false,
+                            body: Some(..),
+                            ..
+                        }
+                    ) =>
                     {
                         if f.params.is_empty() && f.body.as_ref().unwrap().stmts.len() == 1 {
                             if let Stmt::Return(ReturnStmt { arg: Some(arg), .. }) =
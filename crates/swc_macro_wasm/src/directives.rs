@@ -0,0 +1,64 @@
+use swc_common::comments::SingleThreadedComments;
+use swc_common::sync::Lrc;
+use swc_common::{FileName, SourceMap};
+use swc_ecma_ast::EsVersion;
+use swc_ecma_parser::{EsSyntax, Lexer, StringInput, Syntax};
+use swc_macro_parser::MacroParser;
+
+/// List the macros of `namespace` in `source` without evaluating them, for editor tooling
+pub fn list_directives(source: String, namespace: &str) -> serde_json::Value {
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(FileName::Custom("test.js".to_string()).into(), source);
+    let comments = SingleThreadedComments::default();
+    // Only lexed, as the source is often incomplete while being edited. The lexer goes past
+    // syntax errors, and hands all the comments over once the input is exhausted.
+    Lexer::new(
+        Syntax::Es(EsSyntax::default()),
+        EsVersion::latest(),
+        StringInput::from(&*fm),
+        Some(&comments),
+    )
+    .for_each(drop);
+
+    let macros = MacroParser::new(namespace).scan(&comments);
+    macros
+        .into_iter()
+        .map(|(_, macro_node)| {
+            let loc = cm.lookup_char_pos(macro_node.span.lo);
+            serde_json::json!({
+                "namespace": macro_node.namespace,
+                "directive": macro_node.directive,
+                "attrs": macro_node.attrs,
+                "span": { "lo": macro_node.span.lo.0, "hi": macro_node.span.hi.0 },
+                "loc": { "line": loc.line, "col": loc.col.0 + 1 },
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_the_directives_of_an_unparsable_source() {
+        let source = r#"/* @common:if [condition="features.a"] */
+export function a( {
+/* @common:endif */
+const b = /* @common:define-inline [value="build.b"] */ "#;
+
+        let directives = list_directives(source.to_string(), "common");
+
+        let names: Vec<_> = directives
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| d["directive"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["if", "endif", "define-inline"]);
+        assert_eq!(
+            directives[2]["loc"],
+            serde_json::json!({ "line": 4, "col": 11 })
+        );
+    }
+}
@@ -1,11 +1,111 @@
+use swc_macro_condition_transform::{DiagnosticReport, Metadata};
 use wasm_bindgen::prelude::*;
 
+mod banner;
 mod dce;
+pub mod directives;
 pub mod optimize;
+pub mod validate;
 
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console)]
+    fn warn(s: &str);
+}
+
+/// Forward the problems found while optimizing to `console.warn`
+fn warn_output(output: &optimize::Output) {
+    for diagnostic in &output.diagnostics {
+        warn(&format!("[swc_macro] {diagnostic}"));
+    }
+    if !output.dce_converged {
+        warn("[swc_macro] dead code elimination stopped at `maxDceIterations` before settling");
+    }
+}
+
+/// Parse a JSON config, as an `Error` thrown to JS rather than a trap when it is malformed
+fn parse_config(config: &str) -> Result<serde_json::Value, JsError> {
+    serde_json::from_str(config).map_err(|err| JsError::new(&format!("invalid config: {err}")))
+}
+
+/// Returns the optimized code, and throws an `Error` on a malformed config or directives.
+/// See `optimize_with_report` for the stats and diagnostics.
 #[wasm_bindgen]
 pub fn optimize(source: String, config: &str) -> String {
-    let config: serde_json::Value =
-        serde_json::from_str(config).expect("invalid config: must be a json object");
-    optimize::optimize(source, config)
+    let config = parse_config(config).unwrap_or_else(|err| wasm_bindgen::throw_val(err.into()));
+    let output = optimize::optimize(source, config)
+        .unwrap_or_else(|err| wasm_bindgen::throw_str(&err.to_string()));
+    warn_output(&output);
+    output.code
+}
+
+/// The diagnostics of `output` as JSON, for callers like CI that act on them
+fn diagnostics_json(output: &optimize::Output) -> Vec<DiagnosticReport> {
+    output
+        .diagnostics
+        .iter()
+        .map(DiagnosticReport::from)
+        .collect()
+}
+
+/// Like `optimize`, but returns a JSON `{ code, stats, diagnostics, report }`:
+/// - `stats` is `{ macros, removed, dce_converged }`, with the number of directives found and
+///   the nodes removed by them
+/// - `diagnostics` lists the `{ message, span, loc }` of the recoverable problems
+/// - `report` details what the condition transform did
+#[wasm_bindgen]
+pub fn optimize_with_report(source: String, config: &str) -> Result<String, JsError> {
+    let config = parse_config(config)?;
+    let output = optimize::optimize(source, config)?;
+    warn_output(&output);
+    let result = serde_json::json!({
+        "code": output.code,
+        "stats": {
+            "macros": output.macros,
+            "removed": output.report.removed,
+            "dce_converged": output.dce_converged,
+        },
+        "diagnostics": diagnostics_json(&output),
+        "report": output.report,
+    });
+    Ok(result.to_string())
+}
+
+/// Like `optimize`, but returns a JSON `{ code, map, diagnostics }` where `map` is the source map
+/// JSON of `code`
+#[wasm_bindgen]
+pub fn optimize_with_sourcemap(source: String, config: &str) -> Result<String, JsError> {
+    let config = parse_config(config)?;
+    let output = optimize::optimize_with_sourcemap(source, config)?;
+    warn_output(&output);
+    let result = serde_json::json!({
+        "code": output.code,
+        "map": output.source_map,
+        "diagnostics": diagnostics_json(&output),
+    });
+    Ok(result.to_string())
+}
+
+/// List the directives of `namespace` in `source` as JSON, without transforming anything
+#[wasm_bindgen]
+pub fn list_directives(source: String, namespace: &str) -> String {
+    directives::list_directives(source, namespace).to_string()
+}
+
+/// Check the config before `optimize`, returning a JSON array of `{ level, path, message }`,
+/// which is `[]` for a valid config
+#[wasm_bindgen]
+pub fn validate_config(config: String) -> String {
+    serde_json::to_string(&validate::validate_config(&config)).unwrap()
+}
+
+/// Deep merge the JSON config `override_` into `base`, e.g. per-project overrides into global
+/// defaults, returning the merged JSON config. Throws an `Error` naming the malformed input.
+#[wasm_bindgen]
+pub fn merge_config(base: &str, override_: &str) -> Result<String, JsError> {
+    let base: serde_json::Value = serde_json::from_str(base)
+        .map_err(|err| JsError::new(&format!("invalid base config: {err}")))?;
+    let override_: serde_json::Value = serde_json::from_str(override_)
+        .map_err(|err| JsError::new(&format!("invalid override config: {err}")))?;
+    Ok(base.with_override(&override_).to_string())
 }
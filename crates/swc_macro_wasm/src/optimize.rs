@@ -1,5 +1,6 @@
 use swc_common::comments::SingleThreadedComments;
 use swc_common::pass::Repeated;
+use swc_common::source_map::DefaultSourceMapGenConfig;
 use swc_common::sync::Lrc;
 use swc_common::{FileName, Mark, SourceMap};
 use swc_core::ecma::codegen;
@@ -10,10 +11,56 @@ use swc_ecma_codegen::{Emitter, text_writer};
 use swc_ecma_parser::{EsSyntax, Parser, StringInput, Syntax};
 use swc_ecma_transforms_base::fixer::fixer;
 use swc_ecma_transforms_base::resolver;
-use swc_macro_condition_transform::condition_transform;
+use swc_macro_condition_transform::{
+    ConditionTransformError, Diagnostic, Options, RemovedExprReplacement, TransformReport,
+    condition_transform_with_report, constant_folding, inject_env,
+};
 use swc_macro_parser::MacroParser;
 
-pub fn optimize(source: String, config: serde_json::Value) -> String {
+pub struct Output {
+    pub code: String,
+    /// The number of directives of the namespace found in the source
+    pub macros: usize,
+    /// Recoverable problems found in the macros, e.g. a define-inline without any value
+    pub diagnostics: Vec<Diagnostic>,
+    /// What the condition transform did, before the dead code elimination
+    pub report: TransformReport,
+    /// The source map JSON of `code`, only generated by `optimize_with_sourcemap`
+    pub source_map: Option<String>,
+    /// Whether the dead code elimination settled within `maxDceIterations`, otherwise `code` may
+    /// still contain dead code
+    pub dce_converged: bool,
+}
+
+/// The default bound of the dead code elimination rounds, so a pathological input can't hang
+const DCE_MAX_ITERATIONS: usize = 50;
+
+pub fn optimize(
+    source: String,
+    config: serde_json::Value,
+) -> Result<Output, ConditionTransformError> {
+    run(source, config, false, &process_env)
+}
+
+/// Like `optimize`, additionally mapping the output back to `source`
+pub fn optimize_with_sourcemap(
+    source: String,
+    config: serde_json::Value,
+) -> Result<Output, ConditionTransformError> {
+    run(source, config, true, &process_env)
+}
+
+/// Look up a variable of the process environment, which is empty in wasm
+fn process_env(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+fn run(
+    source: String,
+    config: serde_json::Value,
+    with_source_map: bool,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<Output, ConditionTransformError> {
     let cm: Lrc<SourceMap> = Default::default();
     let (mut program, comments) = {
         let fm = cm.new_source_file(FileName::Custom("test.js".to_string()).into(), source);
@@ -28,15 +75,72 @@ pub fn optimize(source: String, config: serde_json::Value) -> String {
         (program, comments)
     };
 
+    let config = if config
+        .get("injectEnv")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+    {
+        let vars = env_vars(&config, env);
+        inject_env(&config, vars)
+    } else {
+        config
+    };
+
+    let max_dce_iterations = config
+        .get("maxDceIterations")
+        .and_then(|v| v.as_u64())
+        .map_or(DCE_MAX_ITERATIONS, |v| v as usize);
+
+    // Folded by default, so the DCE can drop what only an inlined `if (false)` references
+    let fold_constants = config
+        .get("foldConstants")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    // The output is emitted without whitespace unless asked for a readable one. Only the emitter
+    // is compact, nothing is compressed or mangled.
+    let compact = config
+        .get("compact")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let options = Options {
+        removed_expr: match config
+            .get("removedExpressionValue")
+            .and_then(|v| v.as_str())
+        {
+            Some("undefined") => RemovedExprReplacement::Undefined,
+            _ => RemovedExprReplacement::Null,
+        },
+        strict_bool: config
+            .get("strictBool")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        strict: config
+            .get("strictConditions")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    };
+
     let macros = {
-        let parser = MacroParser::new("common");
+        // Only the directives of the namespace are parsed, e.g. `@build:if` for `"build"`
+        let namespace = config
+            .get("namespace")
+            .and_then(|v| v.as_str())
+            .unwrap_or("common");
+        let parser = MacroParser::new(namespace);
 
         parser.parse(&comments)
     };
+    let macro_count = macros.len();
 
-    let program = {
-        let mut transformer = condition_transform(config, macros);
+    let (program, diagnostics, report, dce_converged) = {
+        let (mut transformer, diagnostics, report) =
+            condition_transform_with_report(config, macros, Some(&*cm), options)?;
         program.visit_mut_with(&mut transformer);
+        if fold_constants {
+            program.visit_mut_with(&mut constant_folding());
+        }
 
         // Apply resolver and optimization
         swc_common::GLOBALS.set(&Default::default(), || {
@@ -45,20 +149,32 @@ pub fn optimize(source: String, config: serde_json::Value) -> String {
 
             program.mutate(resolver(unresolved_mark, top_level_mark, false));
 
-            perform_dce(&mut program, comments.clone(), unresolved_mark);
+            let dce_converged = perform_dce(
+                &mut program,
+                comments.clone(),
+                unresolved_mark,
+                max_dce_iterations,
+            );
 
             program.mutate(fixer(Some(&comments)));
 
-            program
+            (program, diagnostics.take(), report.take(), dce_converged)
         })
     };
 
+    crate::banner::relocate_banners(&program, &comments);
+
+    let mut mappings = vec![];
     let ret = {
         let mut buf = vec![];
-        let wr = Box::new(text_writer::JsWriter::new(cm.clone(), "\n", &mut buf, None))
-            as Box<dyn WriteJs>;
+        let wr = Box::new(text_writer::JsWriter::new(
+            cm.clone(),
+            "\n",
+            &mut buf,
+            with_source_map.then_some(&mut mappings),
+        )) as Box<dyn WriteJs>;
         let mut emitter = Emitter {
-            cfg: codegen::Config::default().with_minify(true),
+            cfg: codegen::Config::default().with_minify(compact),
             comments: Some(&comments),
             cm: cm.clone(),
             wr,
@@ -69,10 +185,34 @@ pub fn optimize(source: String, config: serde_json::Value) -> String {
         unsafe { String::from_utf8_unchecked(buf) }
     };
 
-    ret
+    // Nodes created by the transform have no position, so they map to their closest original
+    // neighbours
+    let source_map = with_source_map.then(|| {
+        let mut map = vec![];
+        cm.build_source_map(&mappings, None, DefaultSourceMapGenConfig)
+            .to_writer(&mut map)
+            .unwrap();
+        unsafe { String::from_utf8_unchecked(map) }
+    });
+
+    Ok(Output {
+        code: ret,
+        macros: macro_count,
+        diagnostics,
+        report,
+        source_map,
+        dce_converged,
+    })
 }
 
-fn perform_dce(m: &mut Program, comments: SingleThreadedComments, unresolved_mark: Mark) {
+/// Run the dead code elimination until nothing changes, returning whether that happened within
+/// `max_iterations` rounds
+fn perform_dce(
+    m: &mut Program,
+    comments: SingleThreadedComments,
+    unresolved_mark: Mark,
+    max_iterations: usize,
+) -> bool {
     let mut visitor = crate::dce::dce(
         comments,
         crate::dce::Config {
@@ -84,13 +224,115 @@ fn perform_dce(m: &mut Program, comments: SingleThreadedComments, unresolved_mar
         unresolved_mark,
     );
 
-    loop {
+    for _ in 0..max_iterations {
         m.visit_mut_with(&mut visitor);
 
         if !visitor.changed() {
-            break;
+            return true;
         }
 
         visitor.reset();
     }
+    false
+}
+
+/// The environment variables in `envAllowList` looked up with `env`, nothing else is exposed to
+/// the conditions.
+///
+/// Values in the `envValues` object win over the environment.
+fn env_vars(
+    config: &serde_json::Value,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Vec<(String, serde_json::Value)> {
+    let Some(allow_list) = config.get("envAllowList").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    let values = config.get("envValues");
+
+    allow_list
+        .iter()
+        .filter_map(|name| name.as_str())
+        .filter_map(|name| {
+            let value = values
+                .and_then(|values| values.get(name))
+                .cloned()
+                .or_else(|| env(name).map(serde_json::Value::String))?;
+            Some((name.to_string(), value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// An environment of `vars`, rather than the process environment shared by the parallel tests
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: Vec<(String, String)> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| {
+            vars.iter()
+                .find(|(var, _)| var == name)
+                .map(|(_, value)| value.clone())
+        }
+    }
+
+    #[test]
+    fn env_vars_read_the_allowed_variables() {
+        let env = env(&[("MODE", "production"), ("SECRET", "hidden")]);
+        let config = json!({ "envAllowList": ["MODE", "UNSET"] });
+
+        assert_eq!(
+            env_vars(&config, &env),
+            [("MODE".to_string(), json!("production"))]
+        );
+    }
+
+    #[test]
+    fn env_values_win_over_the_environment() {
+        let env = env(&[("CHANNEL", "stable")]);
+        let config = json!({
+            "envAllowList": ["CHANNEL"],
+            "envValues": { "CHANNEL": "beta" },
+        });
+
+        assert_eq!(
+            env_vars(&config, &env),
+            [("CHANNEL".to_string(), json!("beta"))]
+        );
+    }
+
+    #[test]
+    fn injected_env_resolves_in_conditions() {
+        let env = env(&[("CI", "true"), ("DEBUG", "1")]);
+        let source = r#"
+/* @common:if [condition="env.CI"] */
+export const ci = 1;
+/* @common:endif */
+/* @common:if [condition="env.DEBUG"] */
+export const debug = 1;
+/* @common:endif */
+"#;
+        let config = json!({
+            "injectEnv": true,
+            "envAllowList": ["CI", "DEBUG"],
+            // The configured value wins over the environment variable
+            "env": { "DEBUG": false },
+        });
+
+        let output = run(source.to_string(), config, false, &env).unwrap();
+
+        assert_eq!(output.code, "export const ci=1;");
+    }
+
+    #[test]
+    fn process_env_reads_the_process_variables() {
+        let path = std::env::var("PATH").ok();
+
+        assert_eq!(process_env("PATH"), path);
+    }
 }
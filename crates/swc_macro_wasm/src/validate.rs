@@ -0,0 +1,117 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// A problem in the config given to `optimize`
+#[derive(Debug, Serialize)]
+pub struct ValidationIssue {
+    /// `"error"` when `optimize` would fail or misread the config, `"warning"` otherwise
+    pub level: &'static str,
+    /// The key of the config the issue is about
+    pub path: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn error(path: &str, message: impl Into<String>) -> Self {
+        ValidationIssue {
+            level: "error",
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+
+    fn warning(path: &str, message: impl Into<String>) -> Self {
+        ValidationIssue {
+            level: "warning",
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Check the options read by `optimize` before running it. Other keys are metadata and accepted
+/// as is.
+pub fn validate_config(config: &str) -> Vec<ValidationIssue> {
+    let config: Value = match serde_json::from_str(config) {
+        Ok(config) => config,
+        Err(err) => return vec![ValidationIssue::error("", format!("invalid json: {err}"))],
+    };
+    if !config.is_object() {
+        return vec![ValidationIssue::error(
+            "",
+            "the config must be a json object",
+        )];
+    }
+
+    let mut issues = Vec::new();
+    for key in [
+        "foldConstants",
+        "compact",
+        "strictBool",
+        "strictConditions",
+        "injectEnv",
+    ] {
+        if config.get(key).is_some_and(|v| !v.is_boolean()) {
+            issues.push(ValidationIssue::error(key, "must be a boolean"));
+        }
+    }
+
+    if let Some(namespace) = config.get("namespace") {
+        match namespace.as_str() {
+            Some("") => issues.push(ValidationIssue::error("namespace", "must not be empty")),
+            Some(_) => {}
+            None => issues.push(ValidationIssue::error("namespace", "must be a string")),
+        }
+    }
+
+    if let Some(value) = config.get("removedExpressionValue")
+        && !matches!(value.as_str(), Some("null" | "undefined"))
+    {
+        issues.push(ValidationIssue::error(
+            "removedExpressionValue",
+            "must be \"null\" or \"undefined\"",
+        ));
+    }
+
+    if let Some(value) = config.get("maxDceIterations")
+        && value.as_u64().is_none_or(|v| v == 0)
+    {
+        issues.push(ValidationIssue::error(
+            "maxDceIterations",
+            "must be a positive integer",
+        ));
+    }
+
+    match config.get("envAllowList") {
+        Some(Value::Array(names)) if names.iter().all(Value::is_string) => {}
+        Some(_) => issues.push(ValidationIssue::error(
+            "envAllowList",
+            "must be an array of strings",
+        )),
+        None => {}
+    }
+    if config.get("envValues").is_some_and(|v| !v.is_object()) {
+        issues.push(ValidationIssue::error("envValues", "must be an object"));
+    }
+
+    // Options that only take effect along with another one
+    let inject_env = config
+        .get("injectEnv")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    for key in ["envAllowList", "envValues"] {
+        if config.get(key).is_some() && !inject_env {
+            issues.push(ValidationIssue::warning(
+                key,
+                "is ignored unless `injectEnv` is true",
+            ));
+        }
+    }
+    if inject_env && config.get("envAllowList").is_none() {
+        issues.push(ValidationIssue::warning(
+            "injectEnv",
+            "no variable is injected without `envAllowList`",
+        ));
+    }
+    issues
+}
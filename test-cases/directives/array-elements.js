@@ -0,0 +1,35 @@
+// Conditional elements inside array literals and call arguments
+
+const baseRoutes = ['/home'];
+const adminRoutes = ['/admin', '/admin/users'];
+
+export const routes = [
+  '/',
+  /* @common:if [condition="featureFlags.enableReports"] */
+  '/reports',
+  '/reports/export',
+  /* @common:endif */
+  '/settings',
+];
+
+export const allRoutes = [
+  ...baseRoutes,
+  /* @common:if [condition="featureFlags.enableAdmin"] */
+  ...adminRoutes,
+  /* @common:endif */
+];
+
+export const tail = [
+  'first',
+  /* @common:if [condition="featureFlags.enableReports"] */
+  'last'
+  /* @common:endif */
+];
+
+export const plugins = createPlugins(
+  'core',
+  /* @common:if [condition="featureFlags.enableReports"] */
+  'reports',
+  /* @common:endif */
+  'ui'
+);
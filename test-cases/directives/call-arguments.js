@@ -0,0 +1,33 @@
+// Conditional call arguments
+
+const extraPlugins = ['analytics', 'reports'];
+
+export const plugins = createPlugins(
+  'core',
+  /* @common:if [condition="featureFlags.enableReports"] */
+  'reports',
+  /* @common:endif */
+  'ui'
+);
+
+export const allPlugins = createPlugins(
+  'core',
+  /* @common:if [condition="featureFlags.enableReports"] */
+  ...extraPlugins,
+  /* @common:endif */
+);
+
+export const logger = new Logger(
+  'app',
+  /* @common:if [condition="featureFlags.enableReports" mode="null"] */
+  reportSink,
+  /* @common:endif */
+  'info'
+);
+
+export const positional = setup(
+  /* @common:if [condition="featureFlags.enableReports" mode="null"] */
+  ...extraPlugins,
+  /* @common:endif */
+  'last'
+);
@@ -0,0 +1,25 @@
+// Conditional members inside a class body
+
+export class Editor {
+  /* @common:if [condition="featureFlags.enableCollab"] */
+  static collabServer = 'wss://collab.example.com';
+  /* @common:endif */
+
+  constructor(doc) {
+    this.doc = doc;
+  }
+
+  save() {
+    return this.doc;
+  }
+
+  /* @common:if [condition="featureFlags.enableCollab"] */
+  share() {
+    return Editor.collabServer;
+  }
+  /* @common:endif */
+
+  get title() {
+    return this.doc.title;
+  }
+}
@@ -0,0 +1,28 @@
+// Branches on inlined flags, folded unless `foldConstants` is disabled
+
+function debugOverlay() {
+  return 'debug-overlay';
+}
+
+function modernRender() {
+  return 'modern-render';
+}
+
+function legacyRender() {
+  return 'legacy-render';
+}
+
+export function render() {
+  if (/* @common:define-inline [value="features.debug"] */ DEBUG) {
+    debugOverlay();
+  }
+  if (/* @common:define-inline [value="features.modern"] */ MODERN) {
+    modernRender();
+  } else {
+    legacyRender();
+  }
+  if (false) {
+    console.log('written by hand');
+  }
+  return (/* @common:define-inline [value="features.modern"] */ MODERN) ? 'modern-mode' : 'legacy-mode';
+}
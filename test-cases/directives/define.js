@@ -0,0 +1,13 @@
+// Build-time constants declared once at the module top
+
+/* @common:define [name="BUILD_TARGET", value="build.target"] */
+/* @common:define [name="API_URL", value="api.url", default="http://localhost:3000"] */
+/* @common:define [name="UNUSED_FLAG", value="featureFlags.unused"] */
+
+export function getTarget() {
+  return BUILD_TARGET;
+}
+
+export function getApiUrl() {
+  return API_URL;
+}
@@ -0,0 +1,17 @@
+export function poll(queue) {
+  for (;;);
+  /* @common:if [condition="features.metrics"] */
+  queue.measure();
+  queue.report();
+  /* @common:endif */
+  if (queue.ready) {
+    /* @common:if [condition="features.metrics"] */
+    queue.mark();
+    /* @common:endif */
+  }
+  while (queue.busy) /* @common:if [condition="features.metrics"] */ queue.tick(); /* @common:endif */
+  return queue;
+}
+/* @common:if [condition="features.metrics"] */
+poll.metrics = true;
+/* @common:endif */
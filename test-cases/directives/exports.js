@@ -0,0 +1,36 @@
+// Conditional parts of the public API
+
+function legacyFormat(value) {
+  return 'legacy:' + value;
+}
+
+function modernFormat(value) {
+  return 'modern:' + value;
+}
+
+export {
+  modernFormat,
+  /* @common:if [condition="features.legacy"] */
+  legacyFormat,
+  /* @common:endif */
+};
+
+/* @common:if [condition="features.legacy"] */
+export { legacyFormat as format };
+/* @common:endif */
+
+/* @common:if [condition="features.legacy", keep-decl="true"] */
+export function parseLegacy(value) {
+  return value.split(':')[1];
+}
+/* @common:endif */
+
+export function parse(value) {
+  return parseLegacy(value);
+}
+
+/* @common:if [condition="features.defaultApi"] */
+export default function createApi() {
+  return { parse };
+}
+/* @common:endif */
@@ -0,0 +1,16 @@
+// One-line conditions covering only the next statement
+
+function trackEvent(name) {
+  console.log('track', name);
+}
+
+export function main() {
+  /* @common:if-next [condition="featureFlags.enableTracking"] */
+  trackEvent('start');
+
+  /* @common:if-next [condition="featureFlags.enableTracking"] */
+  var tracker = { name: 'tracker' }, trackerId = 42;
+
+  console.log('main');
+  return typeof tracker + trackerId;
+}
@@ -0,0 +1,31 @@
+// Conditional specifiers inside import declarations
+
+import {
+  formatDate,
+  /* @common:if [condition="features.relativeTime"] */
+  formatRelative,
+  /* @common:endif */
+  formatTime
+} from './format';
+import /* @common:if [condition="features.charts"] */ Chart, /* @common:endif */ { renderTable } from './widgets';
+import {
+  /* @common:if [condition="features.analytics"] */
+  track,
+  identify,
+  /* @common:endif */
+} from './analytics';
+import './polyfills';
+
+export function render(date) {
+  /* @common:if [condition="features.relativeTime"] */
+  console.log(formatRelative(date));
+  /* @common:endif */
+  /* @common:if [condition="features.charts"] */
+  new Chart(date);
+  /* @common:endif */
+  /* @common:if [condition="features.analytics"] */
+  track(date);
+  identify(date);
+  /* @common:endif */
+  return renderTable(formatDate(date) + formatTime(date));
+}
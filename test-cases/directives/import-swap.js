@@ -0,0 +1,12 @@
+// Choose a renderer implementation
+
+/* @common:import-swap [condition="features.newRenderer", from="./renderer-legacy", to="./renderer-next"] */
+import { render } from './renderer-legacy';
+
+/* @common:import-swap [condition="features.newRenderer", from="./renderer-legacy", to="./renderer-next"] */
+const fallback = require('./renderer-legacy');
+
+// Not after a directive
+const legacy = require('./renderer-legacy');
+
+export { render, fallback, legacy };
@@ -0,0 +1,7 @@
+// Instrumentation injected only when enabled
+
+export function startApp(root) {
+  /* @common:inject [condition="features.telemetry", code="telemetry.init"] */
+  root.mount();
+  return root;
+}
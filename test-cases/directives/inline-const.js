@@ -0,0 +1,12 @@
+// Inlining values as constant declarations
+
+import { mode } from './mode';
+
+/* @common:define-inline [as="const" name="VERSION" value="build.version" default="0.0.0"] */
+export function describe() {
+  /* @common:define-inline [as="const" name="TARGET" value="build.target" default="web"] */
+  return `${VERSION} for ${TARGET}`;
+}
+
+/* @common:define-inline [as="const" name="mode" value="build.mode"] */
+export const current = mode;
@@ -0,0 +1,16 @@
+// Legal banners on conditionally removed code
+
+export const ready = true;
+
+/* @common:if [condition="featureFlags.enableAnalytics"] */
+/*! analytics-sdk v2.1.0 | MIT License */
+/* tracking helpers */
+export function track(event) {
+  return event;
+}
+/* @common:endif */
+
+/** @license charts-lib (c) Example Corp */
+function unusedChart() {
+  return 'chart';
+}
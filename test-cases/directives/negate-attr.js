@@ -0,0 +1,23 @@
+// Regions removed when a flag is on
+
+/* @common:if [condition="features.offline" negate="true"] */
+export function syncWithServer() {
+  return 'sync';
+}
+/* @common:endif */
+
+/* @common:if [condition="features.missing" negate="true"] */
+export function missingFlagFallback() {
+  return 'fallback';
+}
+/* @common:endif */
+
+/* @common:unless [condition="features.offline" negate="true"] */
+export function offlineQueue() {
+  return 'queue';
+}
+/* @common:endunless */
+
+export function baseFeature() {
+  return 'base';
+}
@@ -0,0 +1,17 @@
+// Regions kept when a flag is off
+
+/* @common:if [condition="!features.debug"] */
+export function productionLogger() {
+  return 'production';
+}
+/* @common:endif */
+
+/* @common:if [condition="!features.missing"] */
+export function missingFlagFallback() {
+  return 'fallback';
+}
+/* @common:endif */
+
+export function baseFeature() {
+  return 'base';
+}
@@ -0,0 +1,31 @@
+// Three levels of nested conditional regions
+
+export function baseFeature() {
+  return 'base';
+}
+
+/* @common:if [condition="features.outer"] */
+export function outerFeature() {
+  return 'outer';
+}
+
+/* @common:if [condition="features.inner"] */
+export function innerFeature() {
+  return 'inner';
+}
+
+/* @common:if [condition="features.innermost"] */
+export function innermostFeature() {
+  return 'innermost';
+}
+/* @common:endif */
+/* @common:endif */
+
+export function outerTailFeature() {
+  return 'outer tail';
+}
+/* @common:endif */
+
+export function afterFeature() {
+  return 'after';
+}
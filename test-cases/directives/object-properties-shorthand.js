@@ -0,0 +1,22 @@
+// Shorthand and computed properties in removed regions
+
+const featureA = 'a';
+const featureB = 'b';
+const secretKey = 'secret';
+
+export const features = {
+  featureA,
+  /* @common:if [condition="featureFlags.enableB"] */
+  featureB
+  /* @common:endif */
+};
+
+export const lookup = {
+  name: 'lookup',
+  /* @common:if [condition="featureFlags.enableB"] */
+  [secretKey]: featureB,
+  [`${secretKey}-copy`]() {
+    return featureB;
+  },
+  /* @common:endif */
+};
@@ -0,0 +1,20 @@
+// Properties and spreads removed from the middle and the end of object literals
+
+const defaults = { retries: 3 };
+const experimental = { turbo: true };
+
+export const options = {
+  name: 'app',
+  /* @common:if [condition="featureFlags.enableExperimental"] */
+  ...experimental,
+  experimentalLevel: 2,
+  /* @common:endif */
+  ...defaults,
+  version: 1,
+};
+
+export const extras = {
+  /* @common:if [condition="featureFlags.enableExperimental"] */
+  turboMode: true,
+  /* @common:endif */
+};
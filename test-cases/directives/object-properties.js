@@ -0,0 +1,9 @@
+// Conditional properties inside an object literal
+
+export const settings = {
+  theme: 'light',
+  /* @common:if [condition="featureFlags.enableBeta"] */
+  betaPanel: true,
+  /* @common:endif */
+  language: 'en'
+};
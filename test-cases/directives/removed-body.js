@@ -0,0 +1,9 @@
+// Arrow bodies and return arguments in removed regions
+
+export const load = () => /* @common:if [condition="features.preload"] */ expensiveCall() /* @common:endif */;
+
+export const loadWrapped = () => (/* @common:if [condition="features.preload"] */ expensiveCall() /* @common:endif */);
+
+export function compute() {
+  return /* @common:if [condition="features.preload"] */ heavyCompute() /* @common:endif */;
+}
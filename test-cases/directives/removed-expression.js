@@ -0,0 +1,9 @@
+// An expression that can't be dropped from its position
+
+function greet(name = 'guest') {
+  return 'Hello ' + name;
+}
+
+const name = /* @common:if [condition="user.isLoggedIn"] */ getUserName() /* @common:endif */;
+
+export const greeting = greet(name);
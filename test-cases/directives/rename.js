@@ -0,0 +1,19 @@
+// White-label entry points
+/* @common:rename [from="initAcme", to="branding.initName"] */
+
+export function initAcme(options) {
+  return setup(options);
+}
+
+function setup(options) {
+  // Shadowed, not the exported binding
+  const initAcme = options.init;
+  return initAcme();
+}
+
+function boot(initAcme) {
+  return initAcme;
+}
+
+export const registry = { initAcme, start: () => initAcme({}) };
+export { initAcme as initAcme, boot };
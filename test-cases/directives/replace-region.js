@@ -0,0 +1,10 @@
+// A development logger swapped for a configured snippet
+
+export function setupLogger() {
+  /* @common:replace [with="snippets.logger"] */
+  const log = (...args) => console.log('[dev]', ...args);
+  log.enabled = true;
+  /* @common:endreplace */
+  log('ready');
+  return log;
+}
@@ -0,0 +1,11 @@
+// Reporting what the transform did
+
+/* @common:if [condition="features.dashboard"] */
+export function dashboard() {}
+/* @common:endif */
+
+/* @common:unless [condition="features.dashboard"] */
+export function landing() {}
+/* @common:endunless */
+
+export const version = /* @common:define-inline [value="build.version" default="0.0.0"] */ "dev";
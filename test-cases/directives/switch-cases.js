@@ -0,0 +1,35 @@
+// Conditional cases inside switch statements
+
+export function channelLabel(channel) {
+  switch (channel) {
+    case 'stable':
+      return 'stable-label';
+    /* @common:if [condition="featureFlags.enableBeta"] */
+    case 'beta':
+      return 'beta-label';
+    /* @common:endif */
+    case 'nightly':
+      return 'nightly-label';
+    /* @common:if [condition="featureFlags.enableFallback"] */
+    default:
+      return 'fallback-label';
+    /* @common:endif */
+  }
+}
+
+export function planLabel(plan, seats) {
+  switch (plan) {
+    case 'team':
+      switch (seats) {
+        case 1:
+          return 'solo-seat';
+        /* @common:if [condition="featureFlags.enableBeta"] */
+        case 2:
+          return 'pair-seat';
+        /* @common:endif */
+      }
+      return 'team-plan';
+    case 'free':
+      return 'free-plan';
+  }
+}
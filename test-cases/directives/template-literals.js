@@ -0,0 +1,11 @@
+// Inlining into template literals and string text
+
+export const banner = `v${/* @common:define-inline [value="build.version" default="0.0.0"] */ __VERSION__}`;
+
+/* @common:define-inline-text [value="build.version" placeholder="__VERSION__"] */
+export const about = `App __VERSION__ (${`core __VERSION__`})`;
+
+/* @common:define-inline-text [value="build.version" placeholder="__VERSION__"] */
+export const title = 'Release __VERSION__, built from __VERSION__';
+
+export const untouched = 'Release __VERSION__';
@@ -0,0 +1,25 @@
+// Regions opened by leading comments and closed by trailing comments, and the other way round
+
+export function setup(app) {
+  app.init();
+  /* @common:if [condition="features.plugins"] */
+  app.use('router');
+  app.use('store'); /* @common:endif */
+  app.mount(); /* @common:if [condition="features.devtools"] */
+  app.inspect();
+  /* @common:endif */
+  return app;
+}
+
+export const defaults = {
+  /* @common:if [condition="features.plugins"] */
+  plugins: ['router'], /* @common:endif */
+  strict: true,
+};
+
+export function stages(app) {
+  app.load(); /* @common:if [condition="features.plugins"] */
+  app.register(); /* @common:endif */ /* @common:if [condition="features.devtools"] */
+  app.trace(); /* @common:endif */
+  app.start();
+}
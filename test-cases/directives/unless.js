@@ -0,0 +1,23 @@
+// Legacy fallbacks removed once the new implementation is enabled
+
+/* @common:unless [condition="features.modernFetch"] */
+export function legacyFetch() {
+  return 'xhr';
+}
+/* @common:endunless */
+
+/* @common:if [condition="features.modernFetch"] */
+export function modernFetch() {
+  return 'fetch';
+}
+
+/* @common:unless [condition="features.streaming"] */
+export function bufferedResponse() {
+  return 'buffered';
+}
+/* @common:endunless */
+/* @common:endif */
+
+export function baseFeature() {
+  return 'base';
+}
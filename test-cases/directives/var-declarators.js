@@ -0,0 +1,11 @@
+// Declarators removed from multi-declarator statements
+
+export var /* @common:if [condition="features.heavy"] */ first = 1, /* @common:endif */ afterFirst = 2;
+
+export var left = 1, /* @common:if [condition="features.heavy"] */ middle = heavy(), /* @common:endif */ right = 3;
+
+export var beforeLast = 1 /* @common:if [condition="features.heavy"] */, last = heavy() /* @common:endif */;
+
+export let /* @common:if [condition="features.heavy"] */ { width, height } = measure(), /* @common:endif */ scale = 2;
+
+export const /* @common:if [condition="features.heavy"] */ onlyHeavy = heavy() /* @common:endif */;
@@ -0,0 +1,12 @@
+// A region that is closed twice: the second endif has no matching if
+
+/* @common:if [condition="featureFlags.enableFeature"] */
+export function feature() {
+  return 'feature';
+}
+/* @common:endif */
+
+/* @common:endif */
+export function baseFeature() {
+  return 'base';
+}
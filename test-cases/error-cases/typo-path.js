@@ -0,0 +1,7 @@
+// A typo'd flag name in a condition
+
+/* @common:if [condition="featureFlags.enableFeatrue"] */
+export const feature = 'enabled';
+/* @common:endif */
+
+export const mode = /* @common:define-inline [value="build.mdoe"] */ "development";
@@ -0,0 +1,10 @@
+// A region that is never closed
+
+export function baseFeature() {
+  return 'base';
+}
+
+  /* @common:if [condition="featureFlags.enableFeature"] */
+  export function feature() {
+    return 'feature';
+  }
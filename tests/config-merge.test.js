@@ -0,0 +1,56 @@
+import { describe, it, expect, beforeAll } from 'vitest';
+import { optimizer } from './utils/optimizer.js';
+
+describe('Config Merge', () => {
+  beforeAll(async () => {
+    await optimizer.initialize();
+  });
+
+  const defaults = {
+    build: { target: 'development', version: '0.0.0' },
+    featureFlags: { enableNewFeature: false, newMobileUI: true },
+    locales: ['en', 'fr'],
+  };
+
+  it('should keep the nested keys only present in the base', async () => {
+    const overrides = { build: { target: 'production' }, featureFlags: { enableNewFeature: true } };
+
+    const merged = await optimizer.mergeConfig(defaults, overrides);
+
+    expect(merged).toEqual({
+      build: { target: 'production', version: '0.0.0' },
+      featureFlags: { enableNewFeature: true, newMobileUI: true },
+      locales: ['en', 'fr'],
+    });
+  });
+
+  it('should let the override replace arrays and non-object values', async () => {
+    const overrides = { build: 'custom', locales: ['de'], experiment: { group: 'B' } };
+
+    const merged = await optimizer.mergeConfig(defaults, overrides);
+
+    expect(merged).toEqual({
+      build: 'custom',
+      featureFlags: { enableNewFeature: false, newMobileUI: true },
+      locales: ['de'],
+      experiment: { group: 'B' },
+    });
+  });
+
+  it('should optimize with the merged config', async () => {
+    const source = `
+/* @common:if [condition="featureFlags.enableNewFeature"] */
+export const feature = 'new';
+/* @common:endif */
+/* @common:if [condition="featureFlags.newMobileUI"] */
+export const mobile = 'ui';
+/* @common:endif */
+`;
+    const config = await optimizer.mergeConfig(defaults, { featureFlags: { enableNewFeature: true } });
+
+    const optimized = await optimizer.optimizeCode(source, config);
+
+    expect(optimized).toContain('feature');
+    expect(optimized).toContain('mobile');
+  });
+});
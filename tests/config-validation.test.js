@@ -0,0 +1,75 @@
+import { describe, it, expect, beforeAll } from 'vitest';
+import { optimizer } from './utils/optimizer.js';
+
+describe('Config Validation', () => {
+  beforeAll(async () => {
+    await optimizer.initialize();
+  });
+
+  it('should accept a valid config', async () => {
+    const config = {
+      features: { dashboard: true },
+      foldConstants: true,
+      namespace: 'build',
+      removedExpressionValue: 'undefined',
+      injectEnv: true,
+      envAllowList: ['NODE_ENV'],
+    };
+
+    const issues = await optimizer.validateConfig(config);
+
+    expect(issues).toEqual([]);
+  });
+
+  it('should reject invalid json', async () => {
+    const issues = await optimizer.validateConfig('{ "features": ');
+
+    expect(issues).toHaveLength(1);
+    expect(issues[0].level).toBe('error');
+    expect(issues[0].message).toContain('invalid json');
+  });
+
+  it('should report options of the wrong type', async () => {
+    const config = {
+      foldConstants: 'yes',
+      namespace: 42,
+      removedExpressionValue: 'zero',
+      injectEnv: true,
+      maxDceIterations: 0,
+      envAllowList: 'NODE_ENV',
+    };
+
+    const issues = await optimizer.validateConfig(config);
+
+    expect(issues).toEqual([
+      { level: 'error', path: 'foldConstants', message: 'must be a boolean' },
+      { level: 'error', path: 'namespace', message: 'must be a string' },
+      {
+        level: 'error',
+        path: 'removedExpressionValue',
+        message: 'must be "null" or "undefined"',
+      },
+      { level: 'error', path: 'maxDceIterations', message: 'must be a positive integer' },
+      { level: 'error', path: 'envAllowList', message: 'must be an array of strings' },
+    ]);
+  });
+
+  it('should warn about options ignored without injectEnv', async () => {
+    const config = { envAllowList: ['CI'], envValues: { CI: 'true' } };
+
+    const issues = await optimizer.validateConfig(config);
+
+    expect(issues).toEqual([
+      {
+        level: 'warning',
+        path: 'envAllowList',
+        message: 'is ignored unless `injectEnv` is true',
+      },
+      {
+        level: 'warning',
+        path: 'envValues',
+        message: 'is ignored unless `injectEnv` is true',
+      },
+    ]);
+  });
+});
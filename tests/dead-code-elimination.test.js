@@ -1,4 +1,4 @@
-import { describe, it, expect, beforeAll } from 'vitest';
+import { describe, it, expect, beforeAll, vi } from 'vitest';
 import { optimizer } from './utils/optimizer.js';
 import { 
   loadTestCase, 
@@ -328,4 +328,34 @@ describe('Dead Code Elimination Tests', () => {
       expect(allDisabled.analysis.sizes.reductionPercent).toBeGreaterThan(30); // Should remove at least 30% when all disabled
     });
   });
-}); 
\ No newline at end of file
+
+  describe('DCE Iteration Limit', () => {
+    // The reassigned binding is only dropped in a second round
+    const source = `let counter = 1;
+counter = 2;
+export const ready = true;
+`;
+
+    it('should run until nothing changes by default', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+      const optimized = await optimizer.optimizeCode(source, {});
+
+      expect(optimized).not.toContain('counter');
+      expect(warn).not.toHaveBeenCalled();
+      warn.mockRestore();
+    });
+
+    it('should stop and warn once the limit is reached', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+      const optimized = await optimizer.optimizeCode(source, { maxDceIterations: 1 });
+
+      expect(optimized).toContain('let counter=1;');
+      expect(warn).toHaveBeenCalledWith(
+        expect.stringContaining('dead code elimination stopped at `maxDceIterations`')
+      );
+      warn.mockRestore();
+    });
+  });
+});
@@ -0,0 +1,142 @@
+import { describe, it, expect, beforeAll, vi } from 'vitest';
+import { optimizer } from './utils/optimizer.js';
+import { loadTestCase } from './utils/test-helpers.js';
+
+describe('Directive Errors', () => {
+  beforeAll(async () => {
+    await optimizer.initialize();
+  });
+
+  const config = {
+    featureFlags: {
+      enableFeature: true
+    }
+  };
+
+  it('should report an if without a matching endif with its location', async () => {
+    const source = loadTestCase('error-cases', 'unclosed-if.js');
+
+    await expect(optimizer.optimizeCode(source, config))
+      .rejects.toThrow('if at 7:3 has no matching endif');
+  });
+
+  it('should report a stray endif with its location', async () => {
+    const source = loadTestCase('error-cases', 'stray-endif.js');
+
+    await expect(optimizer.optimizeCode(source, config))
+      .rejects.toThrow('endif at 9:1 has no matching if');
+  });
+
+  it('should report an if without a condition attr', async () => {
+    const source = `
+/* @common:if */
+export const a = 1;
+/* @common:endif */
+`;
+
+    await expect(optimizer.optimizeCode(source, config))
+      .rejects.toThrow('if at 2:1 is missing the `condition` attr');
+  });
+
+  it('should report a define-inline without a value attr', async () => {
+    const source = `export const mode = /* @common:define-inline */ "development";`;
+
+    await expect(optimizer.optimizeCode(source, config))
+      .rejects.toThrow('define-inline at 1:21 is missing the `value` attr');
+  });
+
+  it('should warn about an unknown directive and leave the code untouched', async () => {
+    const source = `/* @common:iff [condition="featureFlags.enableFeature"] */
+export const a = 1;
+`;
+    const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+    const optimized = await optimizer.optimizeCode(source, config);
+
+    expect(warn).toHaveBeenCalledWith('[swc_macro] unknown directive `iff` is ignored (at 1:1)');
+    expect(optimized).toContain('a=1');
+    warn.mockRestore();
+  });
+
+  it('should warn about a define-inline without a value or default', async () => {
+    const source = `export const mode = /* @common:define-inline [value="build.mode"] */ "development";`;
+    const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+    await optimizer.optimizeCode(source, config);
+
+    expect(warn).toHaveBeenCalledWith(
+      '[swc_macro] define-inline has no value at `build.mode` and no default (at 1:21)'
+    );
+    warn.mockRestore();
+  });
+
+  describe('Strict Conditions', () => {
+    const source = loadTestCase('error-cases', 'typo-path.js');
+
+    it('should treat a typo\'d path as false when not strict', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('enabled');
+      warn.mockRestore();
+    });
+
+    it('should list every unknown path with suggestions in strict mode', async () => {
+      await expect(optimizer.optimizeCode(source, { ...config, strictConditions: true }))
+        .rejects.toThrow(
+          'unknown metadata paths: `featureFlags.enableFeatrue` in if at 3:1 (did you mean `featureFlags.enableFeature`?), `build.mdoe` in define-inline at 7:21'
+        );
+    });
+
+    it('should suggest a sibling for a typo\'d intermediate object', async () => {
+      const source = `
+/* @common:if [condition="featureFalgs.enableFeature"] */
+export const feature = 'enabled';
+/* @common:endif */
+`;
+
+      await expect(optimizer.optimizeCode(source, { ...config, strictConditions: true }))
+        .rejects.toThrow(
+          'unknown metadata paths: `featureFalgs.enableFeature` in if at 2:1 (did you mean `featureFlags.enableFeature`?)'
+        );
+    });
+
+    it('should list the unknown paths in the report when not strict', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+      const { report } = await optimizer.optimizeCodeWithReport(source, config);
+
+      expect(report.unknown_paths).toEqual([
+        {
+          path: 'featureFlags.enableFeatrue',
+          directive: 'if',
+          suggestions: ['featureFlags.enableFeature'],
+          span: { lo: 39, hi: 96 },
+          loc: { line: 3, col: 1 },
+        },
+        {
+          path: 'build.mdoe',
+          directive: 'define-inline',
+          suggestions: [],
+          span: { lo: 172, hi: 220 },
+          loc: { line: 7, col: 21 },
+        },
+      ]);
+      warn.mockRestore();
+    });
+
+    it('should pass in strict mode when every path is present', async () => {
+      const strictConfig = {
+        featureFlags: { enableFeatrue: false },
+        build: { mdoe: 'production' },
+        strictConditions: true,
+      };
+
+      const { code, report } = await optimizer.optimizeCodeWithReport(source, strictConfig);
+
+      expect(code).toContain('"production"');
+      expect(report.unknown_paths).toEqual([]);
+    });
+  });
+});
@@ -0,0 +1,1863 @@
+import { describe, it, expect, beforeAll, vi } from 'vitest';
+import { optimizer } from './utils/optimizer.js';
+import { loadTestCase } from './utils/test-helpers.js';
+
+describe('Directives', () => {
+  beforeAll(async () => {
+    await optimizer.initialize();
+  });
+
+  describe('Nested Regions', () => {
+    const source = loadTestCase('directives', 'nested-regions.js');
+
+    it('should remove the whole outer region when outer is false and inner is true', async () => {
+      const config = { features: { outer: false, inner: true, innermost: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('outerFeature');
+      expect(optimized).not.toContain('innerFeature');
+      expect(optimized).not.toContain('innermostFeature');
+      expect(optimized).not.toContain('outerTailFeature');
+      expect(optimized).toContain('baseFeature');
+      expect(optimized).toContain('afterFeature');
+    });
+
+    it('should only remove the inner region when outer is true and inner is false', async () => {
+      const config = { features: { outer: true, inner: false, innermost: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('outerFeature');
+      expect(optimized).toContain('outerTailFeature');
+      expect(optimized).not.toContain('innerFeature');
+      expect(optimized).not.toContain('innermostFeature');
+    });
+
+    it('should only remove the innermost region of a triple nesting', async () => {
+      const config = { features: { outer: true, inner: true, innermost: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('outerFeature');
+      expect(optimized).toContain('innerFeature');
+      expect(optimized).toContain('outerTailFeature');
+      expect(optimized).not.toContain('innermostFeature');
+    });
+  });
+
+  describe('Define Inline', () => {
+    const source = `
+export const version = /* @common:define-inline [value="build.version" default="0.0.0"] */ "dev";
+export const target = /* @common:define-inline [value="build.target"] */ "development";
+`;
+
+    it('should replace with the config value when the path is present', async () => {
+      const config = { build: { version: '1.2.3', target: 'production' } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('"1.2.3"');
+      expect(optimized).toContain('"production"');
+    });
+
+    it('should fall back to the default when the path is missing', async () => {
+      const config = { build: { target: 'production' } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('"0.0.0"');
+      expect(optimized).not.toContain('"dev"');
+    });
+
+    it('should warn and leave the expression untouched without a value or default', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+      const config = { build: { version: '1.2.3' } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('"development"');
+      expect(warn).toHaveBeenCalledWith(
+        '[swc_macro] define-inline has no value at `build.target` and no default (at 3:23)'
+      );
+      warn.mockRestore();
+    });
+
+    it('should return the diagnostics alongside the code', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+      const config = { build: { version: '1.2.3' } };
+      const diagnostic = {
+        message: 'define-inline has no value at `build.target` and no default',
+        span: { lo: 122, hi: 172 },
+        loc: { line: 3, col: 23 },
+      };
+
+      const withReport = await optimizer.optimizeCodeWithReport(source, config);
+      const withSourceMap = await optimizer.optimizeCodeWithSourceMap(source, config);
+
+      expect(withReport.diagnostics).toEqual([diagnostic]);
+      expect(withSourceMap.diagnostics).toEqual([diagnostic]);
+      warn.mockRestore();
+    });
+
+    it('should return no diagnostics when every path resolves', async () => {
+      const config = { build: { version: '1.2.3', target: 'production' } };
+
+      const { diagnostics } = await optimizer.optimizeCodeWithReport(source, config);
+
+      expect(diagnostics).toEqual([]);
+    });
+
+    it('should let a later define-inline reference a named one', async () => {
+      const source = `
+export const version = /* @common:define-inline [name="VERSION" value="build.version" default="0.0.0"] */ "dev";
+export const label = /* @common:define-inline [value="inline.VERSION"] */ "dev";
+`;
+
+      expect(await optimizer.optimizeCode(source, { build: { version: '2.1.0' } })).toContain(
+        'export const label="2.1.0"'
+      );
+      // The default of the first directive is chained too
+      expect(await optimizer.optimizeCode(source, {})).toContain('export const label="0.0.0"');
+    });
+
+    it('should inline a null value as null, or as undefined when asked', async () => {
+      const source = `
+export const a = /* @common:define-inline [value="build.channel"] */ "stable";
+export const b = /* @common:define-inline [value="build.channel" null="undefined"] */ "stable";
+export const c = /* @common:define-inline [value="build.extra" null="undefined"] */ {};
+`;
+      const config = { build: { channel: null, extra: { list: [null], tag: null } } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toBe(
+        'export const a=null;export const b=undefined;export const c={"list":[undefined],"tag":undefined};'
+      );
+    });
+
+    it('should only resolve the named values defined earlier in the document', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+      const source = `
+export const label = /* @common:define-inline [value="inline.VERSION"] */ "dev";
+export const version = /* @common:define-inline [name="VERSION" value="build.version"] */ "dev";
+`;
+
+      const optimized = await optimizer.optimizeCode(source, { build: { version: '2.1.0' } });
+
+      expect(optimized).toContain('export const label="dev"');
+      expect(warn).toHaveBeenCalledWith(expect.stringContaining('no value at `inline.VERSION`'));
+      warn.mockRestore();
+    });
+  });
+
+  describe('Define', () => {
+    it('should declare the constants and let DCE remove unused ones', async () => {
+      const source = loadTestCase('directives', 'define.js');
+      const config = { build: { target: 'production' }, featureFlags: { unused: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('const BUILD_TARGET="production"');
+      expect(optimized).toContain('const API_URL="http://localhost:3000"');
+      expect(optimized).not.toContain('UNUSED_FLAG');
+    });
+
+    it('should reject a constant declared twice', async () => {
+      const source = `/* @common:define [name="A", value="a"] */
+/* @common:define [name="A", value="b"] */
+export const b = A;
+`;
+
+      await expect(optimizer.optimizeCode(source, { a: 1, b: 2 }))
+        .rejects.toThrow('define at 2:1 redeclares `A`');
+    });
+  });
+
+  describe('Object Properties', () => {
+    const source = loadTestCase('directives', 'object-properties.js');
+
+    it('should drop the property instead of nulling its value', async () => {
+      const config = { featureFlags: { enableBeta: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('betaPanel');
+      expect(optimized).not.toContain('null');
+      expect(optimized).toContain('theme:"light"');
+      expect(optimized).toContain('language:"en"');
+    });
+
+    it('should count the dropped properties', async () => {
+      const config = { featureFlags: { enableBeta: false } };
+
+      const { stats } = await optimizer.optimizeCodeWithReport(source, config);
+
+      expect(stats.removed.props).toBe(1);
+    });
+
+    it('should keep the property when the condition holds', async () => {
+      const config = { featureFlags: { enableBeta: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('betaPanel:true');
+    });
+
+    it('should drop two of five properties including a spread', async () => {
+      const source = loadTestCase('directives', 'object-properties-spread.js');
+      const config = { featureFlags: { enableExperimental: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('options={name:"app",...defaults,version:1}');
+      expect(optimized).not.toContain('experimental');
+      expect(optimized).not.toContain('null');
+    });
+
+    it('should leave an empty object when all properties are dropped', async () => {
+      const source = loadTestCase('directives', 'object-properties-spread.js');
+      const config = { featureFlags: { enableExperimental: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('extras={}');
+    });
+
+    it('should keep all five properties when the condition holds', async () => {
+      const source = loadTestCase('directives', 'object-properties-spread.js');
+      const config = { featureFlags: { enableExperimental: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain(
+        'options={name:"app",...experimental,experimentalLevel:2,...defaults,version:1}'
+      );
+      expect(optimized).toContain('extras={turboMode:true}');
+    });
+
+    describe('Shorthand And Computed Keys', () => {
+      const source = loadTestCase('directives', 'object-properties-shorthand.js');
+
+      it('should drop a shorthand property that is the last property', async () => {
+        const config = { featureFlags: { enableB: false } };
+
+        const optimized = await optimizer.optimizeCode(source, config);
+
+        expect(optimized).toContain('features={featureA}');
+        expect(optimized).not.toContain('featureB');
+      });
+
+      it('should drop computed properties and methods', async () => {
+        const config = { featureFlags: { enableB: false } };
+
+        const optimized = await optimizer.optimizeCode(source, config);
+
+        expect(optimized).toContain('lookup={name:"lookup"}');
+        expect(optimized).not.toContain('secretKey');
+        expect(optimized).not.toContain('null');
+      });
+
+      it('should keep them when the condition holds', async () => {
+        const config = { featureFlags: { enableB: true } };
+
+        const optimized = await optimizer.optimizeCode(source, config);
+
+        expect(optimized).toContain('features={featureA,featureB}');
+        expect(optimized).toContain(
+          'lookup={name:"lookup",[secretKey]:featureB,[`${secretKey}-copy`](){return featureB;}}'
+        );
+      });
+    });
+  });
+
+  describe('Replace', () => {
+    const source = loadTestCase('directives', 'replace-region.js');
+
+    it('should swap all statements of the region for the snippet statements', async () => {
+      const config = {
+        snippets: { logger: 'const log = () => {}; log.enabled = false;' }
+      };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('[dev]');
+      expect(optimized).not.toContain('log.enabled=true');
+      expect(optimized).toContain('log.enabled=false');
+      expect(optimized).toContain('log("ready")');
+    });
+
+    it('should warn and keep the region when the snippet does not parse', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+      const config = { snippets: { logger: 'const = ;' } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('[dev]');
+      expect(warn).toHaveBeenCalledWith(
+        expect.stringContaining('replace snippet at `snippets.logger` failed to parse')
+      );
+      warn.mockRestore();
+    });
+
+    describe('After A Pending Directive', () => {
+      // The directive inside `g` is still pending when the region after `g` is replaced
+      const sourceWith = (directive, body) => `function g() { /* @common:${directive} */ ${body} }
+/* @common:replace [with="snippets.s"] */
+x();
+/* @common:endreplace */
+g();
+`;
+      const config = { snippets: { s: 'a();' }, f: { t: true, n: false }, build: { v: '1.0' } };
+
+      it('should inject into the function', async () => {
+        const source = sourceWith('inject [condition="f.t", code="snippets.s"]', 'return 1;');
+
+        const optimized = await optimizer.optimizeCode(source, config);
+
+        expect(optimized).toBe('function g(){a();return 1;}a();g();');
+      });
+
+      it('should define in the function', async () => {
+        const source = sourceWith('define [name="V", value="build.v"]', 'return V;');
+
+        const optimized = await optimizer.optimizeCode(source, config);
+
+        expect(optimized).toBe('function g(){const V="1.0";return V;}a();g();');
+      });
+
+      it('should declare an inline constant in the function', async () => {
+        const source = sourceWith('define-inline [value="build.v", name="V", as="const"]', 'return V;');
+
+        const optimized = await optimizer.optimizeCode(source, config);
+
+        expect(optimized).toBe('function g(){const V="1.0";return V;}a();g();');
+      });
+
+      it('should remove the next statement of the function', async () => {
+        const source = sourceWith('if-next [condition="f.n"]', 'b(); return 1;');
+
+        const optimized = await optimizer.optimizeCode(source, config);
+
+        expect(optimized).toBe('function g(){return 1;}a();g();');
+      });
+
+      it('should substitute the text in the function', async () => {
+        const source = sourceWith('define-inline-text [value="build.v", placeholder="__V__"]', 'return "v__V__";');
+
+        const optimized = await optimizer.optimizeCode(source, config);
+
+        expect(optimized).toBe('function g(){return"v1.0";}a();g();');
+      });
+
+      it('should swap the module specifier in the function', async () => {
+        const source = sourceWith('import-swap [condition="f.t", from="./a", to="./b"]', 'return require("./a");');
+
+        const optimized = await optimizer.optimizeCode(source, config);
+
+        expect(optimized).toBe('function g(){return require("./b");}a();g();');
+      });
+    });
+  });
+
+  describe('If Next', () => {
+    const source = loadTestCase('directives', 'if-next.js');
+
+    it('should remove only the next call statement and declaration', async () => {
+      const config = { featureFlags: { enableTracking: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('trackEvent');
+      expect(optimized).not.toContain('trackerId=42');
+      expect(optimized).not.toContain('name:"tracker"');
+      expect(optimized).toContain('console.log("main")');
+    });
+
+    it('should keep everything when the condition holds', async () => {
+      const config = { featureFlags: { enableTracking: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('trackEvent("start")');
+      expect(optimized).toContain('trackerId=42');
+    });
+  });
+
+  describe('Negation', () => {
+    const source = loadTestCase('directives', 'negation.js');
+
+    it('should keep a negated region when the flag is false', async () => {
+      const config = { features: { debug: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('productionLogger');
+    });
+
+    it('should remove a negated region when the flag is true', async () => {
+      const config = { features: { debug: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('productionLogger');
+      expect(optimized).toContain('baseFeature');
+    });
+
+    it('should keep a negated region when the path is missing', async () => {
+      const config = { features: { debug: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('missingFlagFallback');
+    });
+  });
+
+  describe('Negate Attribute', () => {
+    const source = loadTestCase('directives', 'negate-attr.js');
+
+    it('should remove a negated region when the condition is truthy', async () => {
+      const config = { features: { offline: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('syncWithServer');
+      expect(optimized).toContain('baseFeature');
+    });
+
+    it('should keep a negated region when the condition is falsy', async () => {
+      const config = { features: { offline: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('syncWithServer');
+    });
+
+    it('should keep a negated region when the path is missing', async () => {
+      const config = { features: { offline: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('missingFlagFallback');
+    });
+
+    it('should negate an unless back into an if', async () => {
+      const kept = await optimizer.optimizeCode(source, { features: { offline: true } });
+      const removed = await optimizer.optimizeCode(source, { features: { offline: false } });
+
+      expect(kept).toContain('offlineQueue');
+      expect(removed).not.toContain('offlineQueue');
+    });
+  });
+
+  describe('Error And Warn', () => {
+    const source = `/* @common:error [condition="features.a && features.legacyA", message="a and legacyA are mutually exclusive"] */
+/* @common:warn [condition="features.debug", message="debug is enabled"] */
+export const a = 1;
+`;
+
+    it('should fail when an error condition holds', async () => {
+      const config = { features: { a: true, legacyA: true, debug: false } };
+
+      await expect(optimizer.optimizeCode(source, config))
+        .rejects.toThrow('error at 1:1: a and legacyA are mutually exclusive');
+    });
+
+    it('should warn when a warn condition holds', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+      const config = { features: { a: true, legacyA: false, debug: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('@common:');
+      expect(warn).toHaveBeenCalledWith('[swc_macro] debug is enabled (at 2:1)');
+      warn.mockRestore();
+    });
+
+    it('should stay silent when no condition holds', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+      const config = { features: { a: true, legacyA: false, debug: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('a=1');
+      expect(warn).not.toHaveBeenCalled();
+      warn.mockRestore();
+    });
+
+    it('should reject a report directive without a message', async () => {
+      const source = `/* @common:warn [condition="features.debug"] */
+export const a = 1;
+`;
+
+      await expect(optimizer.optimizeCode(source, { features: { debug: true } }))
+        .rejects.toThrow('warn at 1:1 is missing the `message` attr');
+    });
+
+    it('should group the condition with parentheses', async () => {
+      const source = `/* @common:error [condition="features.a && !(features.b || features.c)", message="a needs b or c"] */
+export const a = 1;
+`;
+
+      await expect(optimizer.optimizeCode(source, { features: { a: true, b: false, c: false } }))
+        .rejects.toThrow('error at 1:1: a needs b or c');
+      expect(await optimizer.optimizeCode(source, { features: { a: true, b: false, c: true } }))
+        .toContain('a=1');
+    });
+  });
+
+  describe('Unless', () => {
+    const source = loadTestCase('directives', 'unless.js');
+
+    it('should remove unless regions when the condition holds', async () => {
+      const config = { features: { modernFetch: true, streaming: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('legacyFetch');
+      expect(optimized).not.toContain('bufferedResponse');
+      expect(optimized).toContain('modernFetch');
+    });
+
+    it('should keep unless regions when the condition is falsy', async () => {
+      const config = { features: { modernFetch: false, streaming: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('legacyFetch');
+      // Nested in a removed if region
+      expect(optimized).not.toContain('bufferedResponse');
+      expect(optimized).not.toContain('modernFetch');
+    });
+
+    it('should keep an unless region nested in a kept if region', async () => {
+      const config = { features: { modernFetch: true, streaming: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('bufferedResponse');
+    });
+
+    it('should reject an unless closed by endif', async () => {
+      const source = `/* @common:unless [condition="features.a"] */
+export const a = 1;
+/* @common:endif */
+export const b = 2;
+`;
+
+      await expect(optimizer.optimizeCode(source, { features: { a: true } }))
+        .rejects.toThrow('endif at 3:1 has no matching if');
+    });
+  });
+
+  describe('Inject', () => {
+    const source = loadTestCase('directives', 'inject.js');
+
+    it('should insert the snippet before the next statement when the condition holds', async () => {
+      const config = {
+        features: { telemetry: true },
+        telemetry: { init: 'Telemetry.start("app");' }
+      };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('Telemetry.start("app")');
+      expect(optimized.indexOf('Telemetry.start')).toBeLessThan(optimized.indexOf('root.mount'));
+    });
+
+    it('should not insert the snippet when the condition is falsy', async () => {
+      const config = {
+        features: { telemetry: false },
+        telemetry: { init: 'Telemetry.start("app");' }
+      };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('Telemetry');
+      expect(optimized).toContain('root.mount()');
+    });
+
+    it('should not process macros inside the snippet', async () => {
+      const config = {
+        features: { telemetry: true, verbose: false },
+        telemetry: {
+          init: '/* @common:if [condition="features.verbose"] */ Telemetry.verbose(); /* @common:endif */'
+        }
+      };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('Telemetry.verbose()');
+      expect(optimized).not.toContain('@common');
+    });
+  });
+
+  describe('Constant Folding', () => {
+    const source = loadTestCase('directives', 'constant-branches.js');
+
+    it('should collapse the branches on inlined literals', async () => {
+      const config = { foldConstants: true, features: { debug: false, modern: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      // if (false) {}
+      expect(optimized).not.toContain('debug-overlay');
+      // if (true) { x } else { y }
+      expect(optimized).toContain('modern-render');
+      expect(optimized).not.toContain('legacy-render');
+      // true ? a : b
+      expect(optimized).toContain('modern-mode');
+      expect(optimized).not.toContain('legacy-mode');
+    });
+
+    it('should keep the else branch and the alternate of a false test', async () => {
+      const config = { foldConstants: true, features: { debug: false, modern: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('modern-render');
+      expect(optimized).toContain('legacy-render');
+      // false ? a : b
+      expect(optimized).not.toContain('modern-mode');
+      expect(optimized).toContain('legacy-mode');
+    });
+
+    it('should leave literals written in the source alone', async () => {
+      const config = { foldConstants: true, features: { debug: false, modern: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('written by hand');
+    });
+
+    it('should fold by default and drop what only a dead branch references', async () => {
+      const config = { features: { debug: false, modern: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('debugOverlay');
+      expect(optimized).not.toContain('legacyRender');
+    });
+
+    it('should collapse the branches on inlined numbers', async () => {
+      const source = `
+function tracePanel() {}
+export function init() {
+  if (/* @common:define-inline [value="build.traceLevel"] */ TRACE) {
+    tracePanel();
+  }
+  return (/* @common:define-inline [value="build.retries"] */ RETRIES) || 1;
+}
+`;
+      const config = { build: { traceLevel: 0, retries: 3 } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toBe('export function init(){return 3;}');
+    });
+
+    it('should keep the vars hoisted out of a dropped branch', async () => {
+      const source = `
+if (/* @common:define-inline [value="features.flag"] */ FLAG) { var x = 1; } else { var y = 2; }
+if (/* @common:define-inline [value="features.other"] */ OTHER) { var [z, { w }] = pair(); }
+export function g() { return [x, y, z, w]; }
+`;
+      const config = { compact: false, features: { flag: false, other: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toBe(`{
+    var x;
+    var y = 2;
+}var z, w;
+export function g() {
+    return [
+        x,
+        y,
+        z,
+        w
+    ];
+}
+`);
+    });
+
+    it('should not fold when disabled', async () => {
+      const config = { foldConstants: false, features: { debug: false, modern: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('debug-overlay');
+      expect(optimized).toContain('legacy-render');
+    });
+  });
+
+  describe('Class Members', () => {
+    const source = loadTestCase('directives', 'class-members.js');
+
+    it('should drop a method and a static property in removed regions', async () => {
+      const config = { featureFlags: { enableCollab: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('share()');
+      expect(optimized).not.toContain('collabServer');
+      expect(optimized).toContain('save()');
+      expect(optimized).toContain('get title()');
+    });
+
+    it('should count the dropped members', async () => {
+      const config = { featureFlags: { enableCollab: false } };
+
+      const { stats } = await optimizer.optimizeCodeWithReport(source, config);
+
+      expect(stats.removed.class_members).toBe(2);
+    });
+
+    it('should leave the class untouched when the condition holds', async () => {
+      const config = { featureFlags: { enableCollab: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('static collabServer="wss://collab.example.com"');
+      expect(optimized).toContain('share()');
+      expect(optimized).toContain('save()');
+      expect(optimized).toContain('constructor(doc)');
+    });
+  });
+
+  describe('Switch Cases', () => {
+    const source = loadTestCase('directives', 'switch-cases.js');
+
+    it('should drop a middle case and the default case', async () => {
+      const config = { featureFlags: { enableBeta: false, enableFallback: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('beta');
+      expect(optimized).not.toContain('default');
+      expect(optimized).not.toContain('fallback-label');
+      expect(optimized).toContain('case"stable":return"stable-label";case"nightly":return"nightly-label"');
+    });
+
+    it('should count the dropped cases', async () => {
+      const config = { featureFlags: { enableBeta: false, enableFallback: false } };
+
+      const { stats } = await optimizer.optimizeCodeWithReport(source, config);
+
+      expect(stats.removed.switch_cases).toBe(3);
+    });
+
+    it('should only drop cases of the annotated inner switch', async () => {
+      const config = { featureFlags: { enableBeta: false, enableFallback: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('pair-seat');
+      expect(optimized).toContain('solo-seat');
+      expect(optimized).toContain('team-plan');
+      expect(optimized).toContain('free-plan');
+    });
+
+    it('should keep all cases when the conditions hold', async () => {
+      const config = { featureFlags: { enableBeta: true, enableFallback: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('beta-label');
+      expect(optimized).toContain('pair-seat');
+      expect(optimized).toContain('default:return"fallback-label"');
+    });
+  });
+
+  describe('License Banners', () => {
+    const source = loadTestCase('directives', 'license-banner.js');
+
+    it('should keep the banner of a removed statement at the start of the output', async () => {
+      const config = { featureFlags: { enableAnalytics: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('track');
+      expect(optimized.startsWith('/*! analytics-sdk v2.1.0 | MIT License */')).toBe(true);
+      expect(optimized).not.toContain('tracking helpers');
+    });
+
+    it('should keep the banner of a statement removed by DCE', async () => {
+      const config = { featureFlags: { enableAnalytics: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('unusedChart');
+      expect(optimized).toContain('@license charts-lib (c) Example Corp');
+    });
+
+    it('should leave the banner in place when its statement is kept', async () => {
+      const config = { featureFlags: { enableAnalytics: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized.indexOf('/*! analytics-sdk v2.1.0 | MIT License */')).toBeGreaterThan(
+        optimized.indexOf('ready')
+      );
+      expect(optimized).toContain('tracking helpers');
+    });
+  });
+
+  describe('Array Elements', () => {
+    const source = loadTestCase('directives', 'array-elements.js');
+    const config = { featureFlags: { enableReports: false, enableAdmin: false } };
+
+    it('should drop 2 of 4 elements without holes', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('routes=["/","/settings"]');
+      expect(optimized).not.toContain('null');
+    });
+
+    it('should count the dropped elements', async () => {
+      const { stats } = await optimizer.optimizeCodeWithReport(source, config);
+
+      expect(stats.removed.array_elements).toBe(4);
+    });
+
+    it('should drop a spread element', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('allRoutes=[...baseRoutes]');
+      expect(optimized).not.toContain('adminRoutes');
+    });
+
+    it('should drop the last element without a trailing hole', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('tail=["first"]');
+    });
+
+    it('should drop call arguments', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('createPlugins("core","ui")');
+    });
+
+    it('should keep all elements when the conditions hold', async () => {
+      const config = { featureFlags: { enableReports: true, enableAdmin: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('routes=["/","/reports","/reports/export","/settings"]');
+      expect(optimized).toContain('allRoutes=[...baseRoutes,...adminRoutes]');
+      expect(optimized).toContain('createPlugins("core","reports","ui")');
+    });
+  });
+
+  describe('Call Arguments', () => {
+    const source = loadTestCase('directives', 'call-arguments.js');
+    const config = { featureFlags: { enableReports: false } };
+
+    it('should drop a middle argument', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('createPlugins("core","ui")');
+    });
+
+    it('should count the dropped arguments', async () => {
+      const { stats } = await optimizer.optimizeCodeWithReport(source, config);
+
+      expect(stats.removed.call_args).toBe(2);
+      // The arguments kept with `mode="null"` have their expressions replaced instead
+      expect(stats.removed.exprs).toBe(2);
+    });
+
+    it('should drop a spread argument', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('allPlugins=createPlugins("core")');
+      expect(optimized).not.toContain('extraPlugins');
+    });
+
+    it('should replace the arguments with null in mode="null"', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('new Logger("app",null,"info")');
+      // A spread argument would throw on `...null`
+      expect(optimized).toContain('setup(null,"last")');
+    });
+
+    it('should keep all arguments when the conditions hold', async () => {
+      const config = { featureFlags: { enableReports: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('createPlugins("core","reports","ui")');
+      expect(optimized).toContain('createPlugins("core",...extraPlugins)');
+      expect(optimized).toContain('new Logger("app",reportSink,"info")');
+      expect(optimized).toContain('setup(...extraPlugins,"last")');
+    });
+  });
+
+  describe('Import Specifiers', () => {
+    const source = loadTestCase('directives', 'import-specifiers.js');
+
+    it('should drop one of three specifiers', async () => {
+      const config = { features: { relativeTime: false, charts: true, analytics: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('import{formatDate,formatTime}from"./format"');
+      expect(optimized).not.toContain('formatRelative');
+    });
+
+    it('should count the dropped specifiers', async () => {
+      const config = { features: { relativeTime: false, charts: true, analytics: true } };
+
+      const { stats } = await optimizer.optimizeCodeWithReport(source, config);
+
+      expect(stats.removed.specifiers).toBe(1);
+    });
+
+    it('should drop the whole import when all specifiers are removed', async () => {
+      const config = { features: { relativeTime: true, charts: true, analytics: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('./analytics');
+      // Side effect imports are kept
+      expect(optimized).toContain('import"./polyfills"');
+    });
+
+    it('should drop a default specifier while keeping named ones', async () => {
+      const config = { features: { relativeTime: true, charts: false, analytics: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('import{renderTable}from"./widgets"');
+      expect(optimized).not.toContain('Chart');
+    });
+
+    it('should keep all specifiers when the conditions hold', async () => {
+      const config = { features: { relativeTime: true, charts: true, analytics: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('import{formatDate,formatRelative,formatTime}from"./format"');
+      expect(optimized).toContain('import Chart,{renderTable}from"./widgets"');
+      expect(optimized).toContain('import{track,identify}from"./analytics"');
+    });
+  });
+
+  describe('Exports', () => {
+    const source = loadTestCase('directives', 'exports.js');
+    const config = { features: { legacy: false, defaultApi: false } };
+
+    it('should drop one specifier of a named export and a whole named export', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export{modernFormat}');
+      expect(optimized).not.toContain('legacyFormat');
+      expect(optimized).not.toContain('as format');
+    });
+
+    it('should count the dropped specifiers and exports', async () => {
+      const { stats } = await optimizer.optimizeCodeWithReport(source, config);
+
+      expect(stats.removed.specifiers).toBe(1);
+      expect(stats.removed.module_items).toBe(2);
+    });
+
+    it('should drop an export default with its declaration', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('export default');
+      expect(optimized).not.toContain('createApi');
+    });
+
+    it('should leave a plain function behind with keep-decl', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('function parseLegacy(value)');
+      expect(optimized).not.toContain('export function parseLegacy');
+    });
+
+    it('should keep the exports when the conditions hold', async () => {
+      const config = { features: { legacy: true, defaultApi: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export{modernFormat,legacyFormat}');
+      expect(optimized).toContain('export{legacyFormat as format}');
+      expect(optimized).toContain('export function parseLegacy(value)');
+      expect(optimized).toContain('export default function createApi()');
+    });
+  });
+
+  describe('Trailing Comments', () => {
+    const source = loadTestCase('directives', 'trailing-comments.js');
+
+    it('should remove exactly the statements between leading and trailing comments', async () => {
+      const config = { features: { plugins: false, devtools: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('app.init();app.mount();return app;');
+      expect(optimized).toContain('defaults={strict:true}');
+      expect(optimized).toContain('app.load();app.start();');
+    });
+
+    it('should keep the neighbours of a region closed by a trailing comment', async () => {
+      const config = { features: { plugins: true, devtools: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('app.init();app.use("router");app.use("store");app.mount();return app;');
+      expect(optimized).toContain('defaults={plugins:["router"],strict:true}');
+      expect(optimized).toContain('app.load();app.register();app.start();');
+    });
+
+    it('should keep the neighbours of a region opened by a trailing comment', async () => {
+      const config = { features: { plugins: false, devtools: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('app.init();app.mount();app.inspect();return app;');
+      expect(optimized).toContain('app.load();app.trace();app.start();');
+    });
+  });
+
+  describe('Empty Statements', () => {
+    const source = loadTestCase('directives', 'empty-statements.js');
+    const config = { features: { metrics: false } };
+
+    it('should not leave stray semicolons behind', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toBe(
+        'export function poll(queue){for(;;);if(queue.ready){}while(queue.busy);return queue;}'
+      );
+    });
+
+    it('should keep empty blocks and bodies in readable output', async () => {
+      const optimized = await optimizer.optimizeCode(source, { ...config, compact: false });
+
+      expect(optimized).toBe(`export function poll(queue) {
+    for(;;);
+    if (queue.ready) {}
+    while(queue.busy);
+    return queue;
+}
+`);
+    });
+  });
+
+  describe('Removed Expression Value', () => {
+    const source = loadTestCase('directives', 'removed-expression.js');
+    const evaluateGreeting = (code) =>
+      new Function(`${code.replaceAll('export ', '')}; return greeting;`)();
+
+    it('should replace a removed expression with null by default', async () => {
+      const config = { user: { isLoggedIn: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('const name=null');
+      // null does not trigger the default parameter
+      expect(evaluateGreeting(optimized)).toBe('Hello null');
+    });
+
+    it('should replace a removed expression with void 0 when configured', async () => {
+      const config = { user: { isLoggedIn: false }, removedExpressionValue: 'undefined' };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('const name=void 0');
+      expect(evaluateGreeting(optimized)).toBe('Hello guest');
+    });
+
+    describe('Arrow Bodies And Returns', () => {
+      const source = loadTestCase('directives', 'removed-body.js');
+
+      it('should replace a removed body and return argument with null by default', async () => {
+        const config = { features: { preload: false } };
+
+        const optimized = await optimizer.optimizeCode(source, config);
+
+        expect(optimized).toContain('load=()=>null;');
+        expect(optimized).toContain('loadWrapped=()=>null;');
+        expect(optimized).toContain('return null;');
+      });
+
+      it('should leave an empty body and a bare return when configured', async () => {
+        const config = { features: { preload: false }, removedExpressionValue: 'undefined' };
+
+        const optimized = await optimizer.optimizeCode(source, config);
+
+        expect(optimized).toContain('load=()=>{};');
+        expect(optimized).toContain('loadWrapped=()=>{};');
+        expect(optimized).toContain('function compute(){return;}');
+      });
+
+      it('should keep the body and the return argument when the condition holds', async () => {
+        const config = { features: { preload: true }, removedExpressionValue: 'undefined' };
+
+        const optimized = await optimizer.optimizeCode(source, config);
+
+        expect(optimized).toContain('load=()=>expensiveCall();');
+        expect(optimized).toContain('return heavyCompute();');
+      });
+    });
+  });
+
+  describe('Template Literals', () => {
+    const source = loadTestCase('directives', 'template-literals.js');
+    const evaluate = (code) =>
+      new Function(`${code.replaceAll('export ', '')}; return { banner, about, title, untouched };`)();
+
+    it('should inline a value inside a template literal substitution', async () => {
+      const optimized = await optimizer.optimizeCode(source, { build: { version: '1.2.3' } });
+
+      expect(optimized).not.toContain('@common:define-inline');
+      expect(evaluate(optimized).banner).toBe('v1.2.3');
+    });
+
+    it('should replace every placeholder in the text of nested templates and strings', async () => {
+      const optimized = await optimizer.optimizeCode(source, { build: { version: '1.2.3' } });
+
+      expect(evaluate(optimized)).toEqual({
+        banner: 'v1.2.3',
+        about: 'App 1.2.3 (core 1.2.3)',
+        title: 'Release 1.2.3, built from 1.2.3',
+        untouched: 'Release __VERSION__',
+      });
+    });
+
+    it('should escape the inlined text for strings and templates', async () => {
+      const version = '`${x}` "q" \\ \'s';
+
+      const optimized = await optimizer.optimizeCode(source, { build: { version } });
+
+      expect(evaluate(optimized)).toEqual({
+        banner: `v${version}`,
+        about: `App ${version} (core ${version})`,
+        title: `Release ${version}, built from ${version}`,
+        untouched: 'Release __VERSION__',
+      });
+    });
+
+    it('should warn and keep the placeholder when the value is missing', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+      const optimized = await optimizer.optimizeCode(source, {});
+
+      expect(warn).toHaveBeenCalledWith(expect.stringContaining('define-inline-text has no value'));
+      expect(evaluate(optimized).about).toBe('App __VERSION__ (core __VERSION__)');
+      warn.mockRestore();
+    });
+  });
+
+  describe('Condition Truthiness', () => {
+    const source = `
+/* @common:if [condition="features.version"] */
+export const versioned = true;
+/* @common:endif */
+/* @common:if [condition="features.count"] */
+export const counted = true;
+/* @common:endif */
+`;
+
+    it('should evaluate the values with the truthiness of JavaScript', async () => {
+      const config = { features: { version: '2.0', count: 3 } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('versioned');
+      expect(optimized).toContain('counted');
+    });
+
+    it('should treat empty strings and zero as falsy', async () => {
+      const config = { features: { version: '', count: 0 } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('versioned');
+      expect(optimized).not.toContain('counted');
+    });
+
+    it('should only accept true in strict bool mode', async () => {
+      const config = { features: { version: '2.0', count: 3 }, strictBool: true };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('versioned');
+      expect(optimized).not.toContain('counted');
+    });
+  });
+
+  describe('Inline Constants', () => {
+    const source = loadTestCase('directives', 'inline-const.js');
+
+    it('should declare the constant at module top level', async () => {
+      const config = { build: { version: '2.1.0', target: 'node', mode: 'ssr' } };
+
+      const optimized = await optimizer.optimizeCode(source, { ...config, compact: false });
+
+      expect(optimized).toContain(`const VERSION = "2.1.0";
+export function describe() {`);
+    });
+
+    it('should declare the constant inside a nested block', async () => {
+      const config = { build: { version: '2.1.0', target: 'node', mode: 'ssr' } };
+
+      const optimized = await optimizer.optimizeCode(source, { ...config, compact: false });
+
+      expect(optimized).toContain(`export function describe() {
+    const TARGET = "node";
+    return \`\${VERSION} for \${TARGET}\`;
+}`);
+    });
+
+    it('should warn and skip a constant colliding with an existing binding', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+      const config = { build: { version: '2.1.0', mode: 'ssr' } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('const mode');
+      expect(optimized).not.toContain('"ssr"');
+      expect(warn).toHaveBeenCalledWith(
+        '[swc_macro] define-inline `mode` conflicts with an existing binding and is ignored (at 11:1)'
+      );
+      warn.mockRestore();
+    });
+  });
+
+  describe('Transform Report', () => {
+    const source = loadTestCase('directives', 'report.js');
+
+    it('should report the regions, inlined values and removed nodes', async () => {
+      const config = { features: { dashboard: true } };
+
+      const { code, report } = await optimizer.optimizeCodeWithReport(source, config);
+
+      expect(code).toBe(
+        '// Reporting what the transform did\nexport function dashboard(){}export const version="0.0.0";'
+      );
+      expect(report).toEqual({
+        regions: [
+          {
+            directive: 'if',
+            condition: 'features.dashboard',
+            result: true,
+            kept: true,
+            span: { lo: 88, hi: 194 },
+            loc: { line: 3, col: 1 },
+          },
+          {
+            directive: 'unless',
+            condition: 'features.dashboard',
+            result: true,
+            kept: false,
+            span: { lo: 194, hi: 248 },
+            loc: { line: 7, col: 1 },
+          },
+        ],
+        inlines: [
+          {
+            path: 'build.version',
+            value: '0.0.0',
+            default_used: true,
+            span: { lo: 271, hi: 338 },
+            loc: { line: 11, col: 24 },
+          },
+        ],
+        skipped: [],
+        unknown_paths: [],
+        diagnostics: [],
+        removed: { module_items: 1, stmts: 0, exprs: 0, declarators: 0, props: 0, class_members: 0, switch_cases: 0, array_elements: 0, call_args: 0, specifiers: 0 },
+      });
+    });
+
+    it('should return the stats of the optimization', async () => {
+      const config = { features: { dashboard: true } };
+
+      const { stats, diagnostics } = await optimizer.optimizeCodeWithReport(source, config);
+
+      expect(stats).toEqual({
+        macros: 5,
+        removed: { module_items: 1, stmts: 0, exprs: 0, declarators: 0, props: 0, class_members: 0, switch_cases: 0, array_elements: 0, call_args: 0, specifiers: 0 },
+        dce_converged: true,
+      });
+      expect(diagnostics).toEqual([]);
+    });
+
+    it('should return no macros for a source without directives', async () => {
+      const { code, stats } = await optimizer.optimizeCodeWithReport('export const a = 1;', {});
+
+      expect(code).toBe('export const a=1;');
+      expect(stats.macros).toBe(0);
+      expect(stats.removed).toEqual({ module_items: 0, stmts: 0, exprs: 0, declarators: 0, props: 0, class_members: 0, switch_cases: 0, array_elements: 0, call_args: 0, specifiers: 0 });
+    });
+
+    describe('Diagnostics', () => {
+      const diagnosticsOf = async (source, config) => {
+        const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+        const { report } = await optimizer.optimizeCodeWithReport(source, config);
+        warn.mockRestore();
+        return report.diagnostics.map(({ message, loc }) => ({ message, loc }));
+      };
+
+      it('should report an unknown directive', async () => {
+        const source = `/* @common:iff [condition="features.beta"] */
+export const a = 1;
+`;
+
+        expect(await diagnosticsOf(source, { features: { beta: true } })).toEqual([
+          { message: 'unknown directive `iff` is ignored', loc: { line: 1, col: 1 } },
+        ]);
+      });
+
+      it('should report a define-inline without a value or default', async () => {
+        const source = 'export const a = /* @common:define-inline [value="build.a"] */ 0;';
+
+        expect(await diagnosticsOf(source, {})).toEqual([
+          { message: 'define-inline has no value at `build.a` and no default', loc: { line: 1, col: 18 } },
+        ]);
+      });
+
+      it('should report the second define-inline on the same expression', async () => {
+        const source = `export const a = /* @common:define-inline [value="build.a"] */ /* @common:define-inline [value="build.b"] */ 0;`;
+
+        const { code } = await optimizer.optimizeCodeWithReport(source, { build: { a: 1, b: 2 } });
+
+        expect(code).toBe('export const a=1;');
+        expect(await diagnosticsOf(source, { build: { a: 1, b: 2 } })).toEqual([
+          {
+            message: 'define-inline conflicts with an earlier define-inline on the same expression and is ignored',
+            loc: { line: 1, col: 64 },
+          },
+        ]);
+      });
+
+      it('should report a second rename of the same binding', async () => {
+        const source = `/* @common:rename [from="initAcme", to="branding.a"] */
+/* @common:rename [from="initAcme", to="branding.b"] */
+export function initAcme() {}
+`;
+
+        expect(await diagnosticsOf(source, { branding: { a: 'initA', b: 'initB' } })).toEqual([
+          {
+            message: 'rename `initAcme` conflicts with an earlier rename of it and is ignored',
+            loc: { line: 2, col: 1 },
+          },
+        ]);
+      });
+
+      it('should report a second import-swap of the same specifier', async () => {
+        const source = `/* @common:import-swap [condition="features.a", from="./x", to="./a", scope="module"] */
+/* @common:import-swap [condition="features.b", from="./x", to="./b", scope="module"] */
+import x from './x';
+export { x };
+`;
+        const config = { features: { a: true, b: true } };
+
+        const { code } = await optimizer.optimizeCodeWithReport(source, config);
+
+        expect(code).toContain('from"./a"');
+        expect(await diagnosticsOf(source, config)).toEqual([
+          {
+            message: 'import-swap of `./x` conflicts with an earlier import-swap and is ignored',
+            loc: { line: 2, col: 1 },
+          },
+        ]);
+      });
+
+      it('should report the problems found while applying the pass', async () => {
+        const source = `/* @common:rename [from="initAcme", to="branding.initName"] */
+export function initAcme() {}
+export function initGlobex() {}
+`;
+
+        expect(await diagnosticsOf(source, { branding: { initName: 'initGlobex' } })).toEqual([
+          {
+            message: 'rename `initAcme` to `initGlobex` conflicts with an existing binding and is ignored',
+            loc: { line: 1, col: 1 },
+          },
+        ]);
+      });
+    });
+
+    describe('Define Inline In A Region', () => {
+      const source = `
+/* @common:if [condition="features.beta"] */
+export const channel = /* @common:define-inline [value="build.channel"] */ "dev";
+/* @common:endif */
+`;
+      const build = { channel: 'beta' };
+
+      it('should skip and report a define-inline inside a removed region', async () => {
+        const config = { features: { beta: false }, build };
+
+        const { code, report } = await optimizer.optimizeCodeWithReport(source, config);
+
+        expect(code).toBe('');
+        expect(report.inlines).toEqual([]);
+        expect(report.skipped).toEqual([
+          {
+            directive: 'define-inline',
+            reason: 'inside removed region',
+            span: { lo: 70, hi: 121 },
+            loc: { line: 3, col: 24 },
+          },
+        ]);
+      });
+
+      it('should inline a define-inline inside a kept region', async () => {
+        const config = { features: { beta: true }, build };
+
+        const { code, report } = await optimizer.optimizeCodeWithReport(source, config);
+
+        expect(code).toBe('export const channel="beta";');
+        expect(report.inlines.map((inline) => inline.value)).toEqual(['beta']);
+        expect(report.skipped).toEqual([]);
+      });
+    });
+  });
+
+  describe('Source Maps', () => {
+    const source = loadTestCase('directives', 'report.js');
+
+    it('should return a source map of the optimized code', async () => {
+      const config = { features: { dashboard: true } };
+
+      const { code, map } = await optimizer.optimizeCodeWithSourceMap(source, config);
+      const sourceMap = JSON.parse(map);
+
+      expect(code).toContain('export function dashboard(){}');
+      expect(sourceMap.version).toBe(3);
+      expect(sourceMap.sources).toHaveLength(1);
+      expect(typeof sourceMap.mappings).toBe('string');
+      expect(sourceMap.mappings.length).toBeGreaterThan(0);
+    });
+  });
+
+  describe('Namespace', () => {
+    const source = `
+/* @build:if [condition="features.beta"] */
+export const beta = true;
+/* @build:endif */
+/* @common:if [condition="features.beta"] */
+export const common = true;
+/* @common:endif */
+`;
+
+    it('should only parse the directives of the configured namespace', async () => {
+      const config = { namespace: 'build', features: { beta: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('export const beta');
+      expect(optimized).toContain('export const common=true;');
+    });
+
+    it('should default to the common namespace', async () => {
+      const config = { features: { beta: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export const beta=true;');
+      expect(optimized).not.toContain('export const common');
+    });
+  });
+
+  describe('Environment', () => {
+    const source = `
+export const mode = /* @common:define-inline [value="env.NODE_ENV" default="development"] */ "";
+/* @common:if [condition="env.CI"] */
+export const ci = true;
+/* @common:endif */
+`;
+
+    it('should inject the allowed variables of the provided env object', async () => {
+      const config = {
+        injectEnv: true,
+        envAllowList: ['NODE_ENV', 'CI'],
+        envValues: { NODE_ENV: 'production', CI: 'true' },
+      };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export const mode="production";');
+      expect(optimized).toContain('export const ci=true;');
+    });
+
+    it('should ignore the variables missing from the allow list', async () => {
+      const config = {
+        injectEnv: true,
+        envAllowList: ['NODE_ENV'],
+        envValues: { NODE_ENV: 'production', CI: 'true' },
+      };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export const mode="production";');
+      expect(optimized).not.toContain('export const ci');
+    });
+
+    it('should not inject anything unless enabled', async () => {
+      const config = {
+        envAllowList: ['NODE_ENV', 'CI'],
+        envValues: { NODE_ENV: 'production', CI: 'true' },
+      };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export const mode="development";');
+      expect(optimized).not.toContain('export const ci');
+    });
+
+    it('should prefer the configured values over the environment', async () => {
+      const config = {
+        injectEnv: true,
+        envAllowList: ['NODE_ENV', 'CI'],
+        envValues: { NODE_ENV: 'production', CI: 'true' },
+        env: { NODE_ENV: 'test' },
+      };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export const mode="test";');
+      expect(optimized).toContain('export const ci=true;');
+    });
+  });
+
+  describe('Rename', () => {
+    const source = loadTestCase('directives', 'rename.js');
+    const config = { branding: { initName: 'initGlobex' } };
+
+    it('should rename the binding and its references', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export function initGlobex(options){');
+      expect(optimized).toContain('start:()=>initGlobex({})');
+      expect(optimized).not.toContain('initAcme(options)');
+    });
+
+    it('should keep the property name of a shorthand', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('initAcme:initGlobex');
+    });
+
+    it('should not rename shadowing bindings', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('const initAcme=options.init;return initAcme()');
+      expect(optimized).toContain('function boot(initAcme){return initAcme;}');
+    });
+
+    it('should rename an export specifier', async () => {
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export{initGlobex as initGlobex,boot}');
+    });
+
+    it('should keep the imported name', async () => {
+      const source = `/* @common:rename [from="initAcme" to="branding.initName"] */
+import { initAcme } from "./acme";
+initAcme();
+`;
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('import{initAcme as initGlobex}from"./acme"');
+      expect(optimized).toContain('initGlobex()');
+    });
+
+    it('should ignore a rename captured by a nested binding', async () => {
+      const source = `/* @common:rename [from="initAcme" to="branding.initName"] */
+export function initAcme() {}
+export function run(initGlobex) {
+  return initAcme(initGlobex);
+}
+`;
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export function initAcme(){}');
+      expect(warn).toHaveBeenCalledWith(
+        expect.stringContaining('rename `initAcme` to `initGlobex` would be captured by a nested binding and is ignored')
+      );
+      warn.mockRestore();
+    });
+
+    it('should rename a var redeclared in a top-level block', async () => {
+      const source = `/* @common:rename [from="count" to="branding.counter"] */
+export var count = 1;
+{
+  var count = 2;
+}
+if (count > 1) {
+  var count = count + 1;
+}
+`;
+
+      const optimized = await optimizer.optimizeCode(source, { branding: { counter: 'total' } });
+
+      expect(optimized).toBe('export var total=1;{var total=2;}if(total>1){var total=total+1;}');
+    });
+
+    it('should still leave a let in a block alone', async () => {
+      const source = `/* @common:rename [from="count" to="branding.counter"] */
+export var count = 1;
+{
+  let count = 2;
+  console.log(count);
+}
+`;
+
+      const optimized = await optimizer.optimizeCode(source, { branding: { counter: 'total' } });
+
+      expect(optimized).toBe('export var total=1;{let count=2;console.log(count);}');
+    });
+
+    it('should ignore a rename to a global that is referenced', async () => {
+      const source = `/* @common:rename [from="initAcme" to="branding.initName"] */
+export function initAcme() {
+  return initGlobex();
+}
+`;
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toBe('export function initAcme(){return initGlobex();}');
+      expect(warn).toHaveBeenCalledWith(
+        expect.stringContaining(
+          'rename `initAcme` to `initGlobex` would capture the existing references to the new name and is ignored'
+        )
+      );
+      warn.mockRestore();
+    });
+
+    it('should find a referenced global in a scope shadowing the binding', async () => {
+      const source = `/* @common:rename [from="initAcme" to="branding.initName"] */
+export function initAcme() {}
+export function run(initAcme) {
+  return initGlobex(initAcme);
+}
+`;
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export function initAcme(){}');
+      expect(warn).toHaveBeenCalledWith(
+        expect.stringContaining('would capture the existing references to the new name')
+      );
+      warn.mockRestore();
+    });
+
+    it('should warn about a name that is not an identifier', async () => {
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+      const optimized = await optimizer.optimizeCode(source, { branding: { initName: 'init-globex' } });
+
+      expect(optimized).toContain('export function initAcme(options){');
+      expect(warn).toHaveBeenCalledWith(
+        expect.stringContaining('rename name `init-globex` is not a valid identifier')
+      );
+      warn.mockRestore();
+    });
+  });
+
+  describe('List Directives', () => {
+    const source = loadTestCase('directives', 'report.js');
+
+    it('should list the directives in source order', async () => {
+      const directives = await optimizer.listDirectives(source);
+
+      expect(directives.map((d) => d.directive)).toEqual([
+        'if',
+        'endif',
+        'unless',
+        'endunless',
+        'define-inline',
+      ]);
+      expect(directives[0]).toEqual({
+        namespace: 'common',
+        directive: 'if',
+        attrs: { condition: 'features.dashboard' },
+        span: { lo: 38, hi: 87 },
+        loc: { line: 3, col: 1 },
+      });
+      expect(directives[4].attrs).toEqual({ value: 'build.version', default: '0.0.0' });
+    });
+
+    it('should only list the directives of the namespace', async () => {
+      const directives = await optimizer.listDirectives(source, 'build');
+
+      expect(directives).toEqual([]);
+    });
+
+    it('should list the directives of a source with syntax errors', async () => {
+      const source = `/* @common:if [condition="features.a"] */
+export function a( {
+/* @common:endif */
+const b = /* @common:define-inline [value="build.b"] */`;
+
+      const directives = await optimizer.listDirectives(source);
+
+      expect(directives.map((d) => d.directive)).toEqual(['if', 'endif', 'define-inline']);
+    });
+  });
+
+  describe('Import Swap', () => {
+    const source = loadTestCase('directives', 'import-swap.js');
+
+    it('should swap the source of the next import', async () => {
+      const config = { features: { newRenderer: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('import{render}from"./renderer-next";');
+    });
+
+    it('should swap the argument of the next require call', async () => {
+      const config = { features: { newRenderer: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('const fallback=require("./renderer-next");');
+      expect(optimized).toContain('const legacy=require("./renderer-legacy");');
+    });
+
+    it('should not swap anything when the condition is false', async () => {
+      const config = { features: { newRenderer: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('renderer-next');
+    });
+
+    it('should swap in the whole module with the module scope', async () => {
+      const source = `/* @common:import-swap [condition="features.newRenderer" from="./renderer-legacy" to="./renderer-next" scope="module"] */
+import { render } from "./renderer-legacy";
+export const load = () => import("./renderer-legacy");
+export const chunk = __webpack_require__("./renderer-legacy");
+export { render };
+`;
+
+      const optimized = await optimizer.optimizeCode(source, { features: { newRenderer: true } });
+
+      expect(optimized).not.toContain('renderer-legacy');
+      expect(optimized).toContain('import("./renderer-next")');
+      expect(optimized).toContain('__webpack_require__("./renderer-next")');
+    });
+  });
+
+  describe('Multiple Macros In A Comment', () => {
+    const source = `
+/* @common:if [condition="features.a"] */
+export const a = 1;
+/* @common:endif @common:if [condition="features.b"] */
+export const b = 2;
+/* @common:endif */
+`;
+
+    it('should apply every directive of a comment', async () => {
+      const config = { features: { a: false, b: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toBe('export const b=2;');
+    });
+
+    it('should list every directive of a comment', async () => {
+      const directives = await optimizer.listDirectives(source);
+
+      expect(directives.map((d) => d.directive)).toEqual(['if', 'endif', 'if', 'endif']);
+    });
+
+    it('should keep a comment with directives of another namespace', async () => {
+      const source = `/* @common:if [condition="features.a"] @build:mark [id="a"] */
+export const a = 1;
+/* @common:endif */
+export const b = 2;
+`;
+
+      const optimized = await optimizer.optimizeCode(source, { features: { a: true } });
+
+      expect(optimized).toContain('@build:mark');
+      expect(optimized).toContain('export const a=1;');
+    });
+  });
+
+  describe('Variable Declarators', () => {
+    const source = loadTestCase('directives', 'var-declarators.js');
+
+    it('should drop the first, middle and last declarators', async () => {
+      const config = { features: { heavy: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export var afterFirst=2;');
+      expect(optimized).toContain('export var left=1,right=3;');
+      expect(optimized).toContain('export var beforeLast=1;');
+      expect(optimized).not.toContain('heavy()');
+      expect(optimized).not.toContain('null');
+    });
+
+    it('should drop a destructuring declarator', async () => {
+      const config = { features: { heavy: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export let scale=2;');
+      expect(optimized).not.toContain('measure');
+    });
+
+    it('should drop the statement once no declarator is left', async () => {
+      const config = { features: { heavy: false } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).not.toContain('onlyHeavy');
+      expect(optimized).not.toContain('export const');
+    });
+
+    it('should count the dropped declarators', async () => {
+      const config = { features: { heavy: false } };
+
+      const { stats } = await optimizer.optimizeCodeWithReport(source, config);
+
+      // `onlyHeavy` leaves its statement empty, which counts too
+      expect(stats.removed).toEqual({ module_items: 1, stmts: 0, exprs: 0, declarators: 5, props: 0, class_members: 0, switch_cases: 0, array_elements: 0, call_args: 0, specifiers: 0 });
+    });
+
+    it('should keep the declarator of a for-in or for-of head', async () => {
+      const source = `for (var /* @common:if [condition="features.heavy"] */ k /* @common:endif */ in o) {}
+for (const /* @common:if [condition="features.heavy"] */ [a, b = heavy()] /* @common:endif */ of list) {}
+`;
+      const warn = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+      const { code, report } = await optimizer.optimizeCodeWithReport(source, { features: { heavy: false } });
+      warn.mockRestore();
+
+      expect(code).toBe('for(var k in o){}for(const[a,b=heavy()]of list){}');
+      expect(report.removed.declarators).toBe(0);
+      expect(report.diagnostics.map(({ message, loc }) => ({ message, loc }))).toEqual([
+        { message: "the declarator of a for-in/of head can't be removed and is kept", loc: { line: 1, col: 10 } },
+        { message: "the declarator of a for-in/of head can't be removed and is kept", loc: { line: 2, col: 12 } },
+      ]);
+    });
+
+    it('should keep all declarators when the condition holds', async () => {
+      const config = { features: { heavy: true } };
+
+      const optimized = await optimizer.optimizeCode(source, config);
+
+      expect(optimized).toContain('export var left=1,middle=heavy(),right=3;');
+      expect(optimized).toContain('export let{width,height}=measure(),scale=2;');
+      expect(optimized).toContain('export const onlyHeavy=heavy();');
+    });
+  });
+});
@@ -67,4 +67,24 @@ describe('Isolated Function Call Tests', () => {
     expect(optimized).toContain('main');
     expect(optimized).toContain('baselineFunction');
   });
-}); 
\ No newline at end of file
+
+  it('should pretty print the output when compact is off', async () => {
+    const source = loadTestCase('simple-code', 'isolated-function-call.js');
+    const featureFlags = {
+      enableExperimentalFeature: true,
+      enableAnotherFeature: false
+    };
+
+    const compact = await optimizer.optimizeCode(source, { featureFlags, compact: true });
+    const readable = await optimizer.optimizeCode(source, { featureFlags, compact: false });
+
+    console.log('Compact size:', compact.length);
+    console.log('Readable size:', readable.length);
+    console.log('Compact saves:', readable.length - compact.length, 'bytes');
+
+    expect(compact.length).toBeLessThan(readable.length);
+    expect(readable).toContain('function experimentalFeature() {\n');
+    // Compact by default
+    expect(await optimizer.optimizeCode(source, { featureFlags })).toBe(compact);
+  });
+});
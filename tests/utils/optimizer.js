@@ -9,6 +9,11 @@ import { transformSync } from '@swc/core';
 export class SWCOptimizer {
   constructor() {
     this.optimize = null;
+    this.optimizeWithReport = null;
+    this.optimizeWithSourceMap = null;
+    this.mergeConfigFn = null;
+    this.listDirectivesFn = null;
+    this.validateConfigFn = null;
     this.isInitialized = false;
   }
 
@@ -21,6 +26,11 @@ export class SWCOptimizer {
     // Load the real WASM module - no fallbacks
     const wasmModule = await import('../../crates/swc_macro_wasm/pkg/swc_macro_wasm.js');
     this.optimize = wasmModule.optimize;
+    this.optimizeWithReport = wasmModule.optimize_with_report;
+    this.optimizeWithSourceMap = wasmModule.optimize_with_sourcemap;
+    this.mergeConfigFn = wasmModule.merge_config;
+    this.listDirectivesFn = wasmModule.list_directives;
+    this.validateConfigFn = wasmModule.validate_config;
     console.log('✅ SWC WASM module loaded successfully');
 
     this.isInitialized = true;
@@ -76,6 +86,54 @@ export class SWCOptimizer {
     return this.optimize(transformedSource, JSON.stringify(config));
   }
 
+  /**
+   * Optimize source code and report what the condition transform did
+   * Returns `{ code, stats, diagnostics, report }`
+   */
+  async optimizeCodeWithReport(source, config) {
+    await this.initialize();
+
+    return JSON.parse(this.optimizeWithReport(source, JSON.stringify(config)));
+  }
+
+  /**
+   * Optimize source code and generate the source map of the output
+   * Returns `{ code, map, diagnostics }`, where `map` is the source map JSON string
+   */
+  async optimizeCodeWithSourceMap(source, config) {
+    await this.initialize();
+
+    return JSON.parse(this.optimizeWithSourceMap(source, JSON.stringify(config)));
+  }
+
+  /**
+   * Deep merge config overrides into a base config
+   */
+  async mergeConfig(base, override) {
+    await this.initialize();
+
+    return JSON.parse(this.mergeConfigFn(JSON.stringify(base), JSON.stringify(override)));
+  }
+
+  /**
+   * List the directives of a namespace without transforming the source
+   */
+  async listDirectives(source, namespace = 'common') {
+    await this.initialize();
+
+    return JSON.parse(this.listDirectivesFn(source, namespace));
+  }
+
+  /**
+   * Check a config before optimizing, returning the list of `{ level, path, message }` issues
+   */
+  async validateConfig(config) {
+    await this.initialize();
+
+    const json = typeof config === 'string' ? config : JSON.stringify(config);
+    return JSON.parse(this.validateConfigFn(json));
+  }
+
   /**
    * Analyze the optimization results
    */